@@ -1,430 +1,8026 @@
 //! # excel_database
 //!
 //! A library that lets you perform CRUD operations on an Excel file (`.xlsx`) as if it were a simple database.
-//! Internally, it uses `umya-spreadsheet` to read from and write to XLSX files.
+//! Internally, it uses `umya-spreadsheet` to read from and write to XLSX files, and (with
+//! the `xls` feature) `calamine` to read legacy `.xls` / `.xlsb` workbooks.
 
-use std::collections::HashMap;
-use std::path::Path;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError};
 
+#[cfg(feature = "xls")]
+use calamine::Reader as _;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use indexmap::IndexMap;
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use umya_spreadsheet::{Cell, CellValue as UCellValue, Worksheet};
 use umya_spreadsheet::{reader, writer};
+use umya_spreadsheet::{Cell, CellValue as UCellValue, Pane, PaneStateValues, Spreadsheet};
+use umya_spreadsheet::{Style, Worksheet};
+use unicode_normalization::UnicodeNormalization;
 
-/// Represents a cell's value. Currently, only text is supported.
-/// You can extend this enum to include Number(f64), Bool(bool), Date(String), etc.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Represents a cell's value: text, numbers, booleans, dates, date-times and formulas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CellValue {
     /// Text-based cell
     Text(String),
+    /// Numeric cell
+    Number(f64),
+    /// Boolean cell (`TRUE` / `FALSE` in Excel)
+    Bool(bool),
+    /// Date without a time of day (stored in the sheet as a date-formatted serial number)
+    Date(NaiveDate),
+    /// Date and time without a timezone (stored in the sheet as a date-formatted serial number)
+    DateTime(NaiveDateTime),
+    /// Formula cell: the expression (starting with `=`) and the result Excel last
+    /// calculated for it. Reads, display and `CellValue::compare_loose` go through the
+    /// cached result; `==` and `PartialOrd` also compare the expression.
+    Formula {
+        expression: String,
+        cached_result: Box<CellValue>,
+    },
+    /// A cell with no value, written as a blank cell. Loaded for empty cells with
+    /// `EmptyCellStrategy::Empty`.
+    Empty,
 }
 
-impl From<UCellValue> for CellValue {
-    fn from(raw: UCellValue) -> Self {
-        // Convert any underlying value to a String, then wrap in CellValue::Text
-        let s = raw.get_value().unwrap_or_default().to_string();
-        CellValue::Text(s)
-    }
+/// The variant of a `CellValue`, without its payload.
+///
+/// There are no `Integer` or `Error` types because `CellValue` has no such variants:
+/// whole numbers are stored as `Number`, like every number in an xlsx file, and error cells
+/// such as `#DIV/0!` load as `Text` holding the error code. Use
+/// `ExcelDatabase::infer_column_types` to tell whole numbers from fractional ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellValueType {
+    Text,
+    Number,
+    Bool,
+    Date,
+    DateTime,
+    Formula,
+    Empty,
 }
 
-impl Into<UCellValue> for CellValue {
-    fn into(self) -> UCellValue {
-        match self {
-            CellValue::Text(s) => UCellValue::from(s),
-        }
-    }
+/// The type detected for a column by `ExcelDatabase::infer_column_types`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InferredType {
+    /// Whole numbers (`"42"`, or a `Number` without a fractional part).
+    Integer,
+    /// Numbers, at least one of them fractional.
+    Float,
+    /// `true` / `false` (case-insensitive in text).
+    Boolean,
+    /// Dates or date-times, as values or as date text.
+    Date,
+    /// Text that is none of the above.
+    Text,
+    /// Values of different types.
+    Mixed,
 }
 
-/// A Row is a mapping from column name (String) to its cell value (CellValue).
-pub type Row = HashMap<String, CellValue>;
+/// Format used when displaying `CellValue::Date`.
+const DATE_FORMAT: &str = "%Y-%m-%d";
+/// Format used when displaying `CellValue::DateTime`.
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// Excel number formats applied to cells written from `Date` / `DateTime` values.
+const EXCEL_DATE_FORMAT: &str = "yyyy-mm-dd";
+const EXCEL_DATETIME_FORMAT: &str = "yyyy-mm-dd hh:mm:ss";
+/// Characters added to the longest value when `ExcelDatabase::auto_fit_columns` sizes a column.
+const AUTO_FIT_PADDING: f64 = 2.0;
 
-/// Errors that can occur when working with an ExcelDatabase.
-#[derive(Debug, Error)]
-pub enum ExcelDbError {
-    #[error("I/O error: {0}")]
-    Io(#[from] std::io::Error),
-    #[error("Spreadsheet parsing/writing error: {0}")]
-    SpreadsheetError(#[from] umya_spreadsheet::reader::XlsxError),
-    #[error("Sheet \"{0}\" not found")]
-    SheetNotFound(String),
-    #[error("No headers found in sheet \"{0}\"")]
-    NoHeaders(String),
+/// Day zero of Excel's (1900-based) date serial numbers.
+fn excel_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1899, 12, 30)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .expect("valid epoch")
 }
 
-/// An in-memory representation of an Excel sheet, providing CRUD-like operations.
-pub struct ExcelDatabase {
-    file_path: String,
-    sheet_name: String,
-    data: Vec<Row>,
+/// Convert a date-time to an Excel serial number (days since the epoch, time as a fraction).
+fn datetime_to_serial(dt: NaiveDateTime) -> f64 {
+    (dt - excel_epoch()).num_milliseconds() as f64 / 86_400_000.0
 }
 
-impl ExcelDatabase {
-    /// Create a new ExcelDatabase by loading data from the given file path and sheet name.
-    ///
-    /// # Arguments
-    ///
-    /// * `file_path` - Path to the `.xlsx` file (e.g., `"data.xlsx"`).
-    /// * `sheet_name` - The sheet name to use; if `None`, defaults to `"Sheet1"`.
-    ///
-    /// # Errors
-    ///
-    /// Returns `ExcelDbError::SheetNotFound` if the sheet does not exist, or
-    /// `ExcelDbError::NoHeaders` if the sheet is empty (no header row).
-    pub fn new<P: AsRef<Path>>(
-        file_path: P,
-        sheet_name: Option<String>,
-    ) -> Result<Self, ExcelDbError> {
-        let path_str = file_path.as_ref().to_string_lossy().to_string();
-        let sheet = sheet_name.unwrap_or_else(|| "Sheet1".to_string());
-        let data = Self::load_data(&path_str, &sheet)?;
-        Ok(Self {
-            file_path: path_str,
-            sheet_name: sheet,
-            data,
-        })
+/// Convert an Excel serial number back to a date-time, rounded to the millisecond.
+fn serial_to_datetime(serial: f64) -> Option<NaiveDateTime> {
+    let millis = (serial * 86_400_000.0).round();
+    if !millis.is_finite() || millis.abs() > i64::MAX as f64 {
+        return None;
     }
+    excel_epoch().checked_add_signed(Duration::milliseconds(millis as i64))
+}
 
-    /// Load all rows from the given sheet into memory (`Vec<Row>`).
-    ///
-    /// The first row of the sheet is treated as the header (column names).
-    ///
-    /// # Errors
-    ///
-    /// - `SheetNotFound(sheet_name)` if the sheet is not found.
-    /// - `NoHeaders(sheet_name)` if the sheet has no rows at all.
-    fn load_data(file_path: &str, sheet_name: &str) -> Result<Vec<Row>, ExcelDbError> {
-        // Open the workbook
-        let mut book = reader::xlsx::read(Path::new(file_path))?;
-        if !book.has_sheet(sheet_name) {
-            return Err(ExcelDbError::SheetNotFound(sheet_name.to_string()));
+/// Whether an Excel number format code displays a date (contains a year or day token
+/// outside of quoted literals and `[...]` sections).
+fn is_date_format(code: &str) -> bool {
+    let mut in_quotes = false;
+    let mut in_brackets = false;
+    for c in code.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '[' if !in_quotes => in_brackets = true,
+            ']' if !in_quotes => in_brackets = false,
+            'y' | 'Y' | 'd' | 'D' if !in_quotes && !in_brackets => return true,
+            _ => {}
         }
-        let worksheet = book.get_sheet_by_name(sheet_name).unwrap();
+    }
+    false
+}
 
-        // Collect each row as Vec<CellValue>
-        let mut rows: Vec<Vec<CellValue>> = Vec::new();
-        for row in worksheet.get_row_iter() {
-            let mut row_vals: Vec<CellValue> = Vec::new();
-            for cell in row.get_cell_iter() {
-                let cv: CellValue = cell.get_value().unwrap_or_default().clone().into();
-                row_vals.push(cv);
-            }
-            rows.push(row_vals);
+impl CellValue {
+    /// Which variant this value is.
+    pub fn value_type(&self) -> CellValueType {
+        match self {
+            CellValue::Text(_) => CellValueType::Text,
+            CellValue::Number(_) => CellValueType::Number,
+            CellValue::Bool(_) => CellValueType::Bool,
+            CellValue::Date(_) => CellValueType::Date,
+            CellValue::DateTime(_) => CellValueType::DateTime,
+            CellValue::Formula { .. } => CellValueType::Formula,
+            CellValue::Empty => CellValueType::Empty,
         }
+    }
 
-        // If there are no rows, we cannot infer headers
-        if rows.is_empty() {
-            return Err(ExcelDbError::NoHeaders(sheet_name.to_string()));
+    /// The value a reader sees: a formula's cached result, any other value itself.
+    fn result(&self) -> &CellValue {
+        match self {
+            CellValue::Formula { cached_result, .. } => cached_result.result(),
+            other => other,
         }
+    }
 
-        // The first row is interpreted as header names
-        let headers: Vec<String> = rows[0]
-            .iter()
-            .map(|cv| match cv {
-                CellValue::Text(text) => text.clone(),
-            })
-            .collect();
+    /// Whether the cell holds no data (`Empty`, or blank or whitespace-only text). A formula
+    /// is never empty.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            CellValue::Text(s) => s.trim().is_empty(),
+            CellValue::Empty => true,
+            _ => false,
+        }
+    }
 
-        // Convert subsequent rows into Row maps
-        let mut data: Vec<Row> = Vec::new();
-        for row_vals in rows.into_iter().skip(1) {
-            let mut row_map: Row = HashMap::new();
-            for (col_idx, header) in headers.iter().enumerate() {
-                let value = row_vals
-                    .get(col_idx)
-                    .cloned()
-                    .unwrap_or(CellValue::Text(String::new()));
-                row_map.insert(header.clone(), value);
+    /// Order two values the way queries and `find_max_row` do, which `PartialOrd` does
+    /// not: text that parses as a number on both sides is compared numerically, so `"10"`
+    /// sorts after `"9"`. Across variants, values are compared as numbers (e.g. `Number`
+    /// against numeric text) or as date-times (e.g. `Date` against `DateTime`) when both
+    /// sides allow it; otherwise they are not comparable. Formulas compare by their cached
+    /// result.
+    pub fn compare_loose(&self, other: &Self) -> Option<Ordering> {
+        match (self.result(), other.result()) {
+            (CellValue::Text(a), CellValue::Text(b)) => {
+                match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+                    (Ok(x), Ok(y)) => x.partial_cmp(&y),
+                    _ => a.partial_cmp(b),
+                }
             }
-            data.push(row_map);
+            (CellValue::Bool(a), CellValue::Bool(b)) => a.partial_cmp(b),
+            (CellValue::Empty, CellValue::Empty) => Some(Ordering::Equal),
+            _ => match (self.as_f64(), other.as_f64()) {
+                (Some(x), Some(y)) => x.partial_cmp(&y),
+                _ => match (self.as_datetime(), other.as_datetime()) {
+                    (Some(x), Some(y)) => x.partial_cmp(&y),
+                    _ => None,
+                },
+            },
         }
-
-        Ok(data)
     }
 
-    /// Save the current in-memory `data` back into the Excel file, overwriting the sheet.
-    ///
-    /// # Errors
+    /// Interpret the cell as a date-time.
     ///
-    /// - `SheetNotFound(sheet_name)` if the sheet cannot be found when writing.
-    /// - I/O or spreadsheet errors if the underlying write fails.
-    fn save_data(&self) -> Result<(), ExcelDbError> {
-        let mut book = reader::xlsx::read(Path::new(&self.file_path))?;
-        if !book.has_sheet(&self.sheet_name) {
-            return Err(ExcelDbError::SheetNotFound(self.sheet_name.clone()));
+    /// `Date` values are taken as midnight, formulas by their cached result. Text is accepted
+    /// in `YYYY-MM-DD HH:MM:SS`, `YYYY-MM-DDTHH:MM:SS`, `YYYY-MM-DD` or `YYYY/MM/DD` form.
+    pub fn as_datetime(&self) -> Option<NaiveDateTime> {
+        match self {
+            CellValue::DateTime(dt) => Some(*dt),
+            CellValue::Date(d) => d.and_hms_opt(0, 0, 0),
+            CellValue::Text(s) => {
+                let s = s.trim();
+                ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"]
+                    .iter()
+                    .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+                    .or_else(|| {
+                        ["%Y-%m-%d", "%Y/%m/%d"]
+                            .iter()
+                            .find_map(|f| NaiveDate::parse_from_str(s, f).ok())
+                            .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    })
+            }
+            CellValue::Number(_) | CellValue::Bool(_) | CellValue::Empty => None,
+            CellValue::Formula { cached_result, .. } => cached_result.as_datetime(),
         }
+    }
 
-        // Remove the existing sheet and create a fresh one
-        book.remove_sheet_by_name(&self.sheet_name);
-        let mut new_ws = Worksheet::new();
+    /// A coarse type name for a non-empty value: `"number"`, `"boolean"`, `"date"` or `"text"`.
+    fn kind(&self) -> &'static str {
+        if self.as_f64().is_some() {
+            "number"
+        } else if self.as_bool().is_some() {
+            "boolean"
+        } else if self.as_datetime().is_some() {
+            "date"
+        } else {
+            "text"
+        }
+    }
 
-        // If no rows are present, add an empty sheet
-        if self.data.is_empty() {
-            book.add_worksheet(&self.sheet_name, new_ws);
-            writer::xlsx::write(&book, Path::new(&self.file_path))?;
-            return Ok(());
+    /// Interpret the cell as a number: `Number` values directly, text if it parses as `f64`.
+    /// Formulas are read through their cached result.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.result() {
+            CellValue::Number(n) => Some(*n),
+            CellValue::Text(s) => s.trim().parse::<f64>().ok(),
+            _ => None,
         }
+    }
 
-        // Use the keys from the first Row as headers
-        let headers: Vec<String> = self.data[0].keys().cloned().collect();
+    /// Interpret the cell as a boolean: `Bool` values directly, text if it reads `true` or
+    /// `false` in any letter case.
+    fn as_bool(&self) -> Option<bool> {
+        match self.result() {
+            CellValue::Bool(b) => Some(*b),
+            CellValue::Text(s) if s.trim().eq_ignore_ascii_case("true") => Some(true),
+            CellValue::Text(s) if s.trim().eq_ignore_ascii_case("false") => Some(false),
+            _ => None,
+        }
+    }
 
-        // Write header row (row index 1 in Excel)
-        for (col_idx, header) in headers.iter().enumerate() {
-            let cell = Cell::new((col_idx + 1) as u32, 1, UCellValue::from(header.clone()));
-            new_ws.add_cell(cell);
+    /// The most specific `InferredType` this (non-empty) value satisfies.
+    fn inferred_type(&self) -> InferredType {
+        match self.as_f64() {
+            Some(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => InferredType::Integer,
+            Some(_) => InferredType::Float,
+            None if self.as_bool().is_some() => InferredType::Boolean,
+            None if self.as_datetime().is_some() => InferredType::Date,
+            None => InferredType::Text,
         }
+    }
 
-        // Write actual data rows starting at Excel row 2
-        for (row_idx, row_map) in self.data.iter().enumerate() {
-            let excel_row = (row_idx + 2) as u32; // +2 because Excel is 1-based, and row 1 is header
-            for (col_idx, header) in headers.iter().enumerate() {
-                let value = row_map
-                    .get(header)
-                    .cloned()
-                    .unwrap_or(CellValue::Text(String::new()));
-                let cell_value: UCellValue = value.into();
-                let cell = Cell::new((col_idx + 1) as u32, excel_row, cell_value);
-                new_ws.add_cell(cell);
-            }
+    /// Re-encode the value as the variant matching `target`, or `None` if it cannot be.
+    /// Dates without a time of day become `Date`, others `DateTime`.
+    fn cast(&self, target: InferredType) -> Option<CellValue> {
+        match target {
+            InferredType::Integer => self
+                .as_f64()
+                .filter(|n| n.fract() == 0.0)
+                .map(CellValue::Number),
+            InferredType::Float => self.as_f64().map(CellValue::Number),
+            InferredType::Boolean => self.as_bool().map(CellValue::Bool),
+            InferredType::Date => self.as_datetime().map(|dt| {
+                if dt.time() == chrono::NaiveTime::MIN {
+                    CellValue::Date(dt.date())
+                } else {
+                    CellValue::DateTime(dt)
+                }
+            }),
+            InferredType::Text => Some(CellValue::Text(self.to_string())),
+            InferredType::Mixed => None,
         }
+    }
 
-        // Add the rebuilt sheet and save the file
-        book.add_worksheet(&self.sheet_name, new_ws);
-        writer::xlsx::write(&book, Path::new(&self.file_path))?;
-        Ok(())
+    /// Convert to a plain JSON value: whole numbers become JSON integers (so they fill
+    /// integer fields such as `u32`), other numbers and booleans map directly, blank text
+    /// becomes `null`, and dates are rendered with their `Display` format.
+    fn to_json(&self) -> serde_json::Value {
+        match self.result() {
+            CellValue::Number(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => {
+                serde_json::Value::Number((*n as i64).into())
+            }
+            CellValue::Number(n) => serde_json::Number::from_f64(*n)
+                .map_or(serde_json::Value::Null, serde_json::Value::Number),
+            CellValue::Bool(b) => serde_json::Value::Bool(*b),
+            v if v.is_empty() => serde_json::Value::Null,
+            v => serde_json::Value::String(v.to_string()),
+        }
     }
 
-    /// Reload the sheet data from disk, replacing the in-memory `data`.
-    ///
-    /// # Errors
-    ///
-    /// Propagates any errors from `load_data`.
-    fn refresh_data(&mut self) -> Result<(), ExcelDbError> {
-        self.data = Self::load_data(&self.file_path, &self.sheet_name)?;
-        Ok(())
+    /// Convert a JSON value into a cell. `null` becomes `CellValue::Empty`; arrays and
+    /// objects are kept as their JSON text.
+    fn from_json(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::String(s) => CellValue::Text(s.clone()),
+            serde_json::Value::Number(n) => n
+                .as_f64()
+                .map_or_else(|| CellValue::Text(n.to_string()), CellValue::Number),
+            serde_json::Value::Bool(b) => CellValue::Bool(*b),
+            serde_json::Value::Null => CellValue::Empty,
+            other => CellValue::Text(other.to_string()),
+        }
     }
 
-    // -------------------------------------------------------
-    // Public API: CRUD, lookups, sheet/column management
-    // -------------------------------------------------------
+    /// Read a worksheet cell, turning date-formatted numbers into `Date` / `DateTime` and
+    /// formula cells into `Formula` around their cached result.
+    fn from_cell(cell: &Cell) -> Self {
+        let raw = cell.get_value().unwrap_or_default().clone();
+        if !raw.is_formula() && raw.get_value().unwrap_or_default().is_empty() {
+            return CellValue::Empty;
+        }
+        let expression = raw.is_formula().then(|| format!("={}", raw.get_formula()));
+        let value = Self::from_cell_value(cell, raw.into());
+        match expression {
+            Some(expression) => CellValue::Formula {
+                expression,
+                cached_result: Box::new(value),
+            },
+            None => value,
+        }
+    }
 
-    /// Return all rows that match EVERY key-value pair in `query`.
-    ///
-    /// If `query` is `None`, returns all rows. Returns `None` if no rows match.
-    pub fn select(&self, query: Option<&Row>) -> Option<Vec<Row>> {
-        let mut result: Vec<Row> = Vec::new();
-        let q = query.unwrap_or(&HashMap::new());
-        'outer: for row in self.data.iter() {
-            for (column, wanted) in q.iter() {
-                if let Some(cell_val) = row.get(column) {
-                    if cell_val != wanted {
-                        continue 'outer;
-                    }
-                } else {
-                    continue 'outer;
+    /// Apply the cell's date number format, if any, to a value read from it.
+    fn from_cell_value(cell: &Cell, value: CellValue) -> Self {
+        if let CellValue::Number(serial) = value {
+            let date_formatted = cell
+                .get_style()
+                .get_number_format()
+                .is_some_and(|nf| is_date_format(nf.get_format_code()));
+            if date_formatted {
+                if let Some(value) = Self::from_serial(serial) {
+                    return value;
                 }
             }
-            result.push(row.clone());
         }
-        if result.is_empty() {
-            None
+        value
+    }
+
+    /// A `Date` (or `DateTime`, if it has a time of day) from an Excel serial number.
+    fn from_serial(serial: f64) -> Option<Self> {
+        let dt = serial_to_datetime(serial)?;
+        Some(if dt.time() == chrono::NaiveTime::MIN {
+            CellValue::Date(dt.date())
         } else {
-            Some(result)
-        }
+            CellValue::DateTime(dt)
+        })
     }
 
-    /// Find the first row where `search_column == search_value` and return that row's `target_column` value.
-    pub fn get_column_value(
-        &self,
-        search_column: &str,
-        search_value: &CellValue,
-        target_column: &str,
-    ) -> Option<CellValue> {
-        for row in self.data.iter() {
-            if let Some(val) = row.get(search_column) {
-                if val == search_value {
-                    return row.get(target_column).cloned();
-                }
+    /// Convert a cell read by `calamine`. Errors such as `#DIV/0!` are kept as their text;
+    /// empty cells become `CellValue::Empty`.
+    #[cfg(feature = "xls")]
+    fn from_calamine(data: &calamine::Data) -> Self {
+        match data {
+            calamine::Data::Int(i) => CellValue::Number(*i as f64),
+            calamine::Data::Float(f) => CellValue::Number(*f),
+            calamine::Data::String(s)
+            | calamine::Data::DateTimeIso(s)
+            | calamine::Data::DurationIso(s) => CellValue::Text(s.clone()),
+            calamine::Data::Bool(b) => CellValue::Bool(*b),
+            calamine::Data::DateTime(dt) => {
+                Self::from_serial(dt.as_f64()).unwrap_or(CellValue::Number(dt.as_f64()))
             }
+            calamine::Data::Error(e) => CellValue::Text(e.to_string()),
+            calamine::Data::Empty => CellValue::Empty,
         }
-        None
     }
 
-    /// Insert a new row into the in-memory data and immediately save to the Excel file.
-    ///
-    /// # Errors
-    ///
-    /// Propagates any error from `save_data`.
-    pub fn insert(&mut self, new_row: Row) -> Result<(), ExcelDbError> {
-        self.data.push(new_row);
-        self.save_data()?;
-        Ok(())
+    /// Build a worksheet cell at the given 1-based position, applying a date number format
+    /// for `Date` / `DateTime` values so Excel displays them as dates.
+    fn into_cell(self, col: u32, row: u32) -> Cell {
+        self.into_styled_cell(col, row, None)
     }
 
-    /// Update all rows matching `query` by merging in `update_data`, then save.
-    ///
-    /// # Errors
-    ///
-    /// Propagates any error from `save_data`.
-    pub fn update(&mut self, query: &Row, update_data: &Row) -> Result<(), ExcelDbError> {
-        for row in self.data.iter_mut() {
-            let mut is_match = true;
-            for (key, val) in query.iter() {
-                if let Some(cell_val) = row.get(key) {
-                    if cell_val != val {
-                        is_match = false;
-                        break;
-                    }
+    /// As `into_cell`, but starting from `style` (e.g. that of the cell being overwritten)
+    /// instead of the default style.
+    fn into_styled_cell(self, col: u32, row: u32, style: Option<Style>) -> Cell {
+        let format = match self.result() {
+            CellValue::Date(_) => Some(EXCEL_DATE_FORMAT),
+            CellValue::DateTime(_) => Some(EXCEL_DATETIME_FORMAT),
+            _ => None,
+        };
+        let mut cell = Cell::new(col, row, self.into());
+        if let Some(style) = style {
+            *cell.get_style_mut() = style;
+        }
+        if let Some(code) = format {
+            cell.get_style_mut()
+                .get_number_format_mut()
+                .set_format_code(code);
+        }
+        cell
+    }
+}
+
+/// Values are equal when they have the same variant and content. `Number(NaN)` equals
+/// itself, so it can be used as a hash key and found through an index like any other value.
+impl PartialEq for CellValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CellValue::Text(a), CellValue::Text(b)) => a == b,
+            (CellValue::Number(a), CellValue::Number(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (CellValue::Bool(a), CellValue::Bool(b)) => a == b,
+            (CellValue::Date(a), CellValue::Date(b)) => a == b,
+            (CellValue::DateTime(a), CellValue::DateTime(b)) => a == b,
+            (
+                CellValue::Formula {
+                    expression: a,
+                    cached_result: x,
+                },
+                CellValue::Formula {
+                    expression: b,
+                    cached_result: y,
+                },
+            ) => a == b && x == y,
+            (CellValue::Empty, CellValue::Empty) => true,
+            _ => false,
+        }
+    }
+}
+
+/// `CellValue` is used as a hash key by column indices.
+impl Eq for CellValue {}
+
+impl Hash for CellValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            CellValue::Text(s) => s.hash(state),
+            // `0.0 == -0.0` and every NaN equals every other, so each group must hash alike
+            CellValue::Number(n) => {
+                let n = if *n == 0.0 {
+                    0.0
+                } else if n.is_nan() {
+                    f64::NAN
                 } else {
-                    is_match = false;
-                    break;
-                }
+                    *n
+                };
+                n.to_bits().hash(state)
             }
-            if is_match {
-                for (u_key, u_val) in update_data.iter() {
-                    row.insert(u_key.clone(), u_val.clone());
-                }
+            CellValue::Bool(b) => b.hash(state),
+            CellValue::Date(d) => d.hash(state),
+            CellValue::DateTime(dt) => dt.hash(state),
+            CellValue::Formula {
+                expression,
+                cached_result,
+            } => {
+                expression.hash(state);
+                cached_result.hash(state);
             }
+            CellValue::Empty => {}
         }
-        self.save_data()?;
-        Ok(())
     }
+}
 
-    /// Delete all rows that match `query`, then save back to the file.
-    ///
-    /// # Errors
-    ///
-    /// Propagates any error from `save_data`.
-    pub fn delete(&mut self, query: &Row) -> Result<(), ExcelDbError> {
-        self.data.retain(|row| {
-            for (key, val) in query.iter() {
-                if let Some(cell_val) = row.get(key) {
-                    if cell_val != val {
-                        return true; // keep this row
-                    }
-                } else {
-                    return true; // keep if the column isn't present
-                }
+/// Values of the same variant are ordered by content, and a formula is only comparable to
+/// an identical formula, so the ordering agrees with `==`. Values of different variants are
+/// not comparable; `CellValue::compare_loose` compares them (e.g. `"10"` against `10`).
+impl PartialOrd for CellValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (CellValue::Text(a), CellValue::Text(b)) => a.partial_cmp(b),
+            (CellValue::Number(a), CellValue::Number(b)) if a.is_nan() && b.is_nan() => {
+                Some(Ordering::Equal)
             }
-            false // if all key-value pairs matched, drop this row
-        });
-        self.save_data()?;
-        Ok(())
+            (CellValue::Number(a), CellValue::Number(b)) => a.partial_cmp(b),
+            (CellValue::Bool(a), CellValue::Bool(b)) => a.partial_cmp(b),
+            (CellValue::Date(a), CellValue::Date(b)) => a.partial_cmp(b),
+            (CellValue::DateTime(a), CellValue::DateTime(b)) => a.partial_cmp(b),
+            (CellValue::Empty, CellValue::Empty) => Some(Ordering::Equal),
+            _ if self == other => Some(Ordering::Equal),
+            _ => None,
+        }
     }
+}
 
-    /// Add a new sheet with the given name. If `initial_data` is provided and non-empty,
-    /// its first Row is used for headers, and subsequent Rows fill the sheet.
-    ///
-    /// # Errors
-    ///
-    /// - `SheetNotFound` if a sheet with that name already exists (to avoid overwriting).
-    /// - Propagates I/O or spreadsheet errors if writing fails.
-    pub fn add_sheet(
-        &self,
-        new_sheet_name: &str,
-        initial_data: Option<Vec<Row>>,
-    ) -> Result<(), ExcelDbError> {
-        let mut book = reader::xlsx::read(Path::new(&self.file_path))?;
-        if book.has_sheet(new_sheet_name) {
-            return Err(ExcelDbError::SheetNotFound(new_sheet_name.to_string()));
+impl fmt::Display for CellValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellValue::Text(s) => write!(f, "{}", s),
+            CellValue::Number(n) => write!(f, "{}", n),
+            CellValue::Bool(b) => write!(f, "{}", b),
+            CellValue::Date(d) => write!(f, "{}", d.format(DATE_FORMAT)),
+            CellValue::DateTime(dt) => write!(f, "{}", dt.format(DATETIME_FORMAT)),
+            CellValue::Formula { cached_result, .. } => write!(f, "{}", cached_result),
+            CellValue::Empty => Ok(()),
         }
-        let mut ws = Worksheet::new();
+    }
+}
 
-        if let Some(rows) = initial_data {
-            if !rows.is_empty() {
-                // Use keys from the first row as headers
-                let headers: Vec<String> = rows[0].keys().cloned().collect();
-                // Write header row
-                for (col_idx, header) in headers.iter().enumerate() {
-                    let cell = Cell::new((col_idx + 1) as u32, 1, UCellValue::from(header.clone()));
-                    ws.add_cell(cell);
-                }
-                // Write the data rows
-                for (row_idx, row_map) in rows.iter().enumerate() {
-                    let excel_row = (row_idx + 2) as u32; // Start at row 2
-                    for (col_idx, header) in headers.iter().enumerate() {
-                        let value = row_map
-                            .get(header)
-                            .cloned()
-                            .unwrap_or(CellValue::Text(String::new()));
-                        let cell: Cell =
-                            Cell::new((col_idx + 1) as u32, excel_row, UCellValue::from(match value {
-                                CellValue::Text(s) => s,
-                            }));
-                        ws.add_cell(cell);
-                    }
-                }
+impl From<UCellValue> for CellValue {
+    fn from(raw: UCellValue) -> Self {
+        let s = raw.get_value().unwrap_or_default().to_string();
+        match raw.get_data_type() {
+            "n" => raw
+                .get_value_number()
+                .map_or(CellValue::Text(s), CellValue::Number),
+            "b" => CellValue::Bool(matches!(s.trim(), "1" | "TRUE" | "true")),
+            // Strings, inline strings, errors, etc. all come back as text
+            _ => CellValue::Text(s),
+        }
+    }
+}
+
+impl From<CellValue> for UCellValue {
+    fn from(value: CellValue) -> Self {
+        match value {
+            CellValue::Text(s) => UCellValue::from(s),
+            CellValue::Number(n) => UCellValue::from(n),
+            CellValue::Bool(b) => UCellValue::from(b),
+            CellValue::Date(d) => UCellValue::from(datetime_to_serial(
+                d.and_hms_opt(0, 0, 0).expect("midnight is valid"),
+            )),
+            CellValue::DateTime(dt) => UCellValue::from(datetime_to_serial(dt)),
+            CellValue::Formula {
+                expression,
+                cached_result,
+            } => {
+                let mut raw = UCellValue::from(*cached_result);
+                raw.set_formula(expression.trim_start_matches('='));
+                raw
             }
+            CellValue::Empty => UCellValue::default(),
         }
+    }
+}
 
-        book.add_worksheet(new_sheet_name, ws);
-        writer::xlsx::write(&book, Path::new(&self.file_path))?;
-        Ok(())
+/// A property that the values of a date column should satisfy, checked by
+/// `ExcelDatabase::validate_date_sequence`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateSequenceCheck {
+    /// Each date must be later than the one before it.
+    StrictlyAscending,
+    /// Each date must be earlier than the one before it.
+    StrictlyDescending,
+    /// No date may appear more than once.
+    NoDuplicates,
+    /// Consecutive dates may not be further apart than the given duration.
+    NoGapsLargerThan(Duration),
+}
+
+/// Direction for each key passed to `ExcelDatabase::sort_by` / `ExcelDatabase::sorted`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// A boolean filter over rows, evaluated by `ExcelDatabase::query`.
+///
+/// A `(column, value)` condition holds when the row has `column` equal to `value`;
+/// a missing column never matches.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryOp {
+    /// Every condition must hold (an empty list matches all rows).
+    And(Vec<(String, CellValue)>),
+    /// At least one condition must hold (an empty list matches no rows).
+    Or(Vec<(String, CellValue)>),
+    /// The inner filter must not hold.
+    Not(Box<QueryOp>),
+    /// Both inner filters must hold.
+    And2(Box<QueryOp>, Box<QueryOp>),
+    /// Either inner filter must hold.
+    Or2(Box<QueryOp>, Box<QueryOp>),
+}
+
+impl QueryOp {
+    /// Whether `row` satisfies this filter.
+    fn matches(&self, row: &Row) -> bool {
+        let holds = |(column, wanted): &(String, CellValue)| row.get(column) == Some(wanted);
+        match self {
+            QueryOp::And(conditions) => conditions.iter().all(holds),
+            QueryOp::Or(conditions) => conditions.iter().any(holds),
+            QueryOp::Not(inner) => !inner.matches(row),
+            QueryOp::And2(a, b) => a.matches(row) && b.matches(row),
+            QueryOp::Or2(a, b) => a.matches(row) || b.matches(row),
+        }
     }
+}
 
-    /// Check if a sheet with `sheet_name` exists in the workbook.
-    ///
-    /// # Errors
-    ///
-    /// Propagates any I/O or spreadsheet parsing errors.
-    pub fn is_sheet_exists(&self, sheet_name: &str) -> Result<bool, ExcelDbError> {
-        let book = reader::xlsx::read(Path::new(&self.file_path))?;
-        Ok(book.has_sheet(sheet_name))
+/// An RGB color used for cell formatting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RgbColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl RgbColor {
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
     }
 
-    /// Get a list of all sheet names in the Excel file.
-    ///
-    /// # Errors
-    ///
-    /// Propagates any I/O or spreadsheet parsing errors.
-    pub fn get_all_sheet_names(&self) -> Result<Vec<String>, ExcelDbError> {
-        let book = reader::xlsx::read(Path::new(&self.file_path))?;
-        Ok(book.get_sheet_names().to_vec())
+    /// The color as an opaque `AARRGGBB` hex string, as stored in XLSX styles.
+    pub fn to_argb(&self) -> String {
+        format!("FF{:02X}{:02X}{:02X}", self.r, self.g, self.b)
     }
+}
 
-    /// Count how many non-empty values exist in the specified column across all rows.
-    pub fn get_column_datas_number(&self, column_name: &str) -> usize {
-        self.data
-            .iter()
-            .filter(|row| {
-                if let Some(CellValue::Text(s)) = row.get(column_name) {
-                    !s.trim().is_empty()
-                } else {
-                    false
-                }
-            })
-            .count()
+/// Formatting applied to the header row cells whenever the sheet is saved.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HeaderStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub background: Option<RgbColor>,
+    pub font_color: Option<RgbColor>,
+    pub font_size: Option<f64>,
+}
+
+impl HeaderStyle {
+    /// Copy these settings onto an `umya-spreadsheet` cell style.
+    fn apply_to(&self, style: &mut Style) {
+        let font = style.get_font_mut();
+        font.set_bold(self.bold);
+        font.set_italic(self.italic);
+        if let Some(size) = self.font_size {
+            font.set_size(size);
+        }
+        if let Some(color) = self.font_color {
+            font.get_color_mut().set_argb(color.to_argb());
+        }
+        if let Some(color) = self.background {
+            style.set_background_color(color.to_argb());
+        }
     }
+}
 
-    /// Add a new column with the given default value (or empty string if `None`).
-    /// Only rows that do not already have this column get the default.
-    ///
-    /// # Errors
+/// Formatting for a single data cell, set with `ExcelDatabase::set_cell_style`. Colors are
+/// `(red, green, blue)`. It can also be passed to `set_header_style`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CellStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub font_color: Option<(u8, u8, u8)>,
+    pub background_color: Option<(u8, u8, u8)>,
+}
+
+impl From<CellStyle> for HeaderStyle {
+    fn from(style: CellStyle) -> Self {
+        let rgb = |(r, g, b): (u8, u8, u8)| RgbColor::new(r, g, b);
+        HeaderStyle {
+            bold: style.bold,
+            italic: style.italic,
+            background: style.background_color.map(rgb),
+            font_color: style.font_color.map(rgb),
+            font_size: None,
+        }
+    }
+}
+
+/// Layout options for `ExcelDatabase::generate_report`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportConfig {
+    /// Written in a merged, bold, large-font row at the top of the report.
+    pub title: String,
+    /// Optional line written in a merged row under the title.
+    pub subtitle: Option<String>,
+    /// Style of the table's header row.
+    pub header_style: HeaderStyle,
+    /// Background of every other data row (the 2nd, 4th, ...); `None` disables striping.
+    pub stripe_color: Option<RgbColor>,
+    /// Optional line written in a merged row below the table.
+    pub footer: Option<String>,
+}
+
+impl ReportConfig {
+    /// A report with the given title, a bold grey table header and light grey striping.
+    pub fn new<S: Into<String>>(title: S) -> Self {
+        Self {
+            title: title.into(),
+            subtitle: None,
+            header_style: HeaderStyle {
+                bold: true,
+                italic: false,
+                background: Some(RgbColor::new(0xD9, 0xD9, 0xD9)),
+                font_color: None,
+                font_size: None,
+            },
+            stripe_color: Some(RgbColor::new(0xF2, 0xF2, 0xF2)),
+            footer: None,
+        }
+    }
+}
+
+/// Expected type of a column in a `Schema`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnType {
+    /// Any non-empty value.
+    Text,
+    /// A `Number`, or text that parses as a number.
+    Number,
+    /// A `Bool`, or the text `true` / `false` in any letter case.
+    Bool,
+    /// The inner type, but the cell may also be blank or missing.
+    Optional(Box<ColumnType>),
+}
+
+impl ColumnType {
+    /// Check `value` (`None` for a missing cell) against this type, describing the problem if any.
+    fn check(&self, column: &str, value: Option<&CellValue>) -> Result<(), String> {
+        let value = match (self, value.filter(|v| !v.is_empty())) {
+            (ColumnType::Optional(_), None) => return Ok(()),
+            (_, None) => return Err(format!("column \"{}\" is required", column)),
+            (_, Some(value)) => value,
+        };
+        let ok = match self {
+            ColumnType::Text => true,
+            ColumnType::Number => value.as_f64().is_some(),
+            ColumnType::Bool => value.as_bool().is_some(),
+            ColumnType::Optional(inner) => return inner.check(column, Some(value)),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(format!(
+                "column \"{}\" expects {:?} but has \"{}\"",
+                column, self, value
+            ))
+        }
+    }
+}
+
+/// Column rules that rows must satisfy before `insert` / `update` change the data
+/// (see `ExcelDatabase::set_schema`), built with `Schema::new().require(..).build()`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Schema {
+    columns: Vec<(String, ColumnType)>,
+    defaults: Vec<(String, CellValue)>,
+}
+
+impl Schema {
+    /// An empty schema that accepts every row.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require `column` to hold values of `column_type` (use `ColumnType::Optional` to
+    /// allow blanks). A later rule for the same column replaces the earlier one.
+    pub fn require<S: Into<String>>(mut self, column: S, column_type: ColumnType) -> Self {
+        let column = column.into();
+        self.columns.retain(|(c, _)| *c != column);
+        self.columns.push((column, column_type));
+        self
+    }
+
+    /// Use `value` for `column` when `ExcelDatabase::insert_with_defaults` gets a row
+    /// without it. A later default for the same column replaces the earlier one.
+    pub fn default_value<S: Into<String>>(mut self, column: S, value: CellValue) -> Self {
+        let column = column.into();
+        self.defaults.retain(|(c, _)| *c != column);
+        self.defaults.push((column, value));
+        self
+    }
+
+    /// Finish building the schema.
+    pub fn build(self) -> Self {
+        self
+    }
+
+    /// Every rule `row` breaks, as human-readable messages.
+    fn violations(&self, row: &Row) -> Vec<String> {
+        self.columns
+            .iter()
+            .filter_map(|(column, column_type)| column_type.check(column, row.get(column)).err())
+            .collect()
+    }
+}
+
+/// A predicate registered with `ExcelDatabase::add_column_validator`.
+#[derive(Clone)]
+struct ColumnValidator(Arc<dyn Fn(&CellValue) -> bool + Send + Sync>);
+
+impl fmt::Debug for ColumnValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ColumnValidator(..)")
+    }
+}
+
+/// Data-quality summary produced by `ExcelDatabase::health_check`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HealthReport {
+    /// Per column, the number of rows where the value is blank or missing.
+    pub null_counts: HashMap<String, usize>,
+    /// Number of rows that exactly repeat an earlier row.
+    pub duplicate_row_count: usize,
+    /// Number of rows that lack at least one of the sheet's columns.
+    pub ragged_row_count: usize,
+    /// Per column, whether all non-empty values share one inferred type
+    /// (number, boolean, date or text).
+    pub type_consistency: HashMap<String, bool>,
+    /// Header names that are empty or whitespace-only.
+    pub empty_column_names: Vec<String>,
+}
+
+/// Result of a column aggregation such as `ExcelDatabase::sum_column`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AggResult {
+    /// The aggregated value over the numeric cells.
+    pub value: f64,
+    /// Number of non-empty cells that were skipped because they are not numeric.
+    pub skipped: usize,
+}
+
+/// Which row of each duplicate group `ExcelDatabase::deduplicate` keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepStrategy {
+    /// Keep the earliest row of the group.
+    First,
+    /// Keep the latest row of the group.
+    Last,
+}
+
+/// How column names are compared, set with `ExcelDatabase::with_case_sensitivity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseSensitivity {
+    /// `"Name"` and `"name"` are different columns (the default).
+    #[default]
+    Sensitive,
+    /// Column names are compared in lowercase, so `"name"` finds the `"Name"` column.
+    Insensitive,
+}
+
+/// What loading does with merged cell ranges, set with `ExcelDatabaseBuilder::merged_cells`.
+///
+/// Excel stores a merged range's value only in its top-left cell; the other cells read as
+/// empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergedCellHandling {
+    /// Copy the top-left value into every cell of the range. Saving then writes that value
+    /// into every cell of the range.
+    Propagate,
+    /// Leave the other cells of the range empty (the default).
+    #[default]
+    Ignore,
+    /// Refuse to load a sheet that has merged cells.
+    Error,
+}
+
+/// What loading does with empty cells, set with `ExcelDatabaseBuilder::empty_cell_strategy`.
+///
+/// A cell counts as empty when the sheet has no cell there or the cell has no value (an
+/// empty field, for `.csv` files). Text cells holding `""` or only whitespace are not empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyCellStrategy {
+    /// Read empty cells as `CellValue::Text("")`.
+    EmptyString,
+    /// Leave empty cells out of the `Row`, so the column's key is missing.
+    OmitKey,
+    /// Skip every row that has an empty cell. The database stays writable, but saving
+    /// writes back only the rows that were loaded, so the skipped rows leave the sheet.
+    SkipRow,
+    /// Read empty cells as `CellValue::Empty`, so they save back as blank cells (the
+    /// default).
+    #[default]
+    Empty,
+}
+
+impl EmptyCellStrategy {
+    /// Apply the strategy to freshly loaded rows, whose empty cells are `CellValue::Empty`.
+    fn apply(self, rows: Vec<Row>) -> Vec<Row> {
+        rows.into_iter()
+            .filter_map(|row| self.apply_to_row(row))
+            .collect()
+    }
+
+    /// Apply the strategy to one freshly loaded row, or `None` if `SkipRow` skips it.
+    fn apply_to_row(self, mut row: Row) -> Option<Row> {
+        let is_empty = |v: &CellValue| matches!(v, CellValue::Empty);
+        match self {
+            EmptyCellStrategy::EmptyString => {
+                for value in row.values_mut().filter(|v| is_empty(v)) {
+                    *value = CellValue::Text(String::new());
+                }
+            }
+            EmptyCellStrategy::OmitKey => row.retain(|_, v| !is_empty(v)),
+            EmptyCellStrategy::SkipRow if row.values().any(is_empty) => return None,
+            EmptyCellStrategy::SkipRow | EmptyCellStrategy::Empty => {}
+        }
+        Some(row)
+    }
+}
+
+/// What loading does when two columns have the same header name, set with
+/// `ExcelDatabaseBuilder::duplicate_headers`. Blank header cells never count as duplicates:
+/// each is named after its 1-based column position, e.g. `Column_3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateHeaderStrategy {
+    /// Refuse to load the sheet with `ExcelDbError::DuplicateHeader` (the default).
+    #[default]
+    Error,
+    /// Keep every column, renaming the 2nd, 3rd, ... occurrence to `name_2`, `name_3`, ...
+    RenameWithSuffix,
+    /// Keep the leftmost column of each name and skip the others.
+    KeepFirst,
+    /// Keep the rightmost column of each name and skip the others.
+    KeepLast,
+}
+
+/// The file format saves write, set with `ExcelDatabase::set_save_format`.
+///
+/// Only `Xlsx` keeps everything; `Csv` and `Json` write just the active sheet's rows,
+/// without formatting or the other sheets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SaveFormat {
+    /// Replace the sheet in the `.xlsx` workbook (the default).
+    #[default]
+    Xlsx,
+    /// RFC 4180 CSV with a header row, written with a `.csv` extension. Values become text.
+    Csv,
+    /// A JSON array of row objects as written by `ExcelDatabase::to_json_string`, written
+    /// with a `.json` extension.
+    Json,
+}
+
+impl SaveFormat {
+    /// The format a file is read and written in, judged by its extension (`.csv`, `.json`,
+    /// anything else `.xlsx`).
+    fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("csv") => SaveFormat::Csv,
+            Some("json") => SaveFormat::Json,
+            _ => SaveFormat::Xlsx,
+        }
+    }
+}
+
+/// What `ExcelDatabase::upsert` did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertResult {
+    /// No row matched the key, so the row was inserted.
+    Inserted,
+    /// This many existing rows matched the key and were updated.
+    Updated(usize),
+}
+
+/// One modification recorded while change tracking is on
+/// (see `ExcelDatabase::enable_change_tracking`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// A row was inserted.
+    Insert(Row),
+    /// A row was updated from `before` to `after`.
+    Update { before: Row, after: Row },
+    /// A row was deleted.
+    Delete(Row),
+}
+
+/// Summary statistics for one column, produced by `ExcelDatabase::column_stats`.
+///
+/// The numeric fields only cover numeric cells and are all `0.0` if there are none.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnStats {
+    /// Number of rows in the sheet.
+    pub total_rows: usize,
+    /// Number of rows with a non-empty value in the column.
+    pub non_empty: usize,
+    /// Number of non-empty values that are numeric.
+    pub numeric_count: usize,
+    /// Sum of the numeric values.
+    pub sum: f64,
+    /// Mean of the numeric values.
+    pub mean: f64,
+    /// Sample standard deviation (`0.0` with fewer than two numeric values).
+    pub std_dev: f64,
+    /// Smallest numeric value.
+    pub min: f64,
+    /// Largest numeric value.
+    pub max: f64,
+    /// Number of distinct non-empty values.
+    pub unique_count: usize,
+}
+
+/// One page of results from `ExcelDatabase::select_page`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    /// The matching rows on this page (empty past the last page).
+    pub rows: Vec<Row>,
+    /// Number of rows matching the query across all pages.
+    pub total_matching: usize,
+    /// The requested page number (0-based).
+    pub page: usize,
+    /// The requested page size.
+    pub page_size: usize,
+    /// Number of pages needed to hold every matching row.
+    pub total_pages: usize,
+}
+
+/// Shape of a sheet, read by `ExcelDatabase::sheet_metadata` without loading its rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetMetadata {
+    /// Number of data rows (every row below the header).
+    pub row_count: usize,
+    /// Number of columns in the header row.
+    pub column_count: usize,
+    /// Header names, left to right.
+    pub column_names: Vec<String>,
+}
+
+/// State of an auto-increment ID column.
+#[derive(Debug, Clone)]
+struct AutoIncrement {
+    column: String,
+    last_value: u64,
+}
+
+/// A Row is a mapping from column name (String) to its cell value (CellValue).
+///
+/// Entries keep their insertion order, which for loaded rows is the sheet's column order,
+/// so saving writes the columns back in the same sequence.
+pub type Row = IndexMap<String, CellValue>;
+
+/// Zero-based `(column, row)` of the top-left and bottom-right cells of a range.
+type CellRange = ((usize, usize), (usize, usize));
+
+/// Errors that can occur when working with an ExcelDatabase.
+#[derive(Debug, Error)]
+pub enum ExcelDbError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Spreadsheet parsing/writing error: {0}")]
+    SpreadsheetError(#[from] umya_spreadsheet::reader::XlsxError),
+    #[error("Sheet \"{0}\" not found")]
+    SheetNotFound(String),
+    #[error("No headers found in sheet \"{0}\"")]
+    NoHeaders(String),
+    #[error("Database was opened in read-only mode")]
+    ReadOnly,
+    #[error("Column \"{0}\" not found")]
+    ColumnNotFound(String),
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+    #[error("Column \"{0}\" contains values that are not dates")]
+    NotADateColumn(String),
+    #[error("Column \"{0}\" has no numeric values")]
+    NotANumericColumn(String),
+    #[error("Row index {0} is out of bounds")]
+    IndexOutOfBounds(usize),
+    #[error("Invalid regex pattern: {0}")]
+    InvalidPattern(#[from] regex::Error),
+    #[error("CSV error: {0}")]
+    CsvError(#[from] csv::Error),
+    #[error("Zip error: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+    #[error("Column \"{0}\" already exists")]
+    DuplicateColumn(String),
+    #[error("Could not convert row: {0}")]
+    DeserializeError(String),
+    #[error("Invalid page request: {0}")]
+    InvalidPage(String),
+    #[error("File already exists: {}", .0.display())]
+    FileAlreadyExists(PathBuf),
+    #[error("Cannot remove the active sheet \"{0}\"")]
+    CannotRemoveActiveSheet(String),
+    #[error("JSON error: {0}")]
+    JsonError(String),
+    #[error("Column order is missing columns: {0:?}")]
+    MissingColumnsInOrder(Vec<String>),
+    #[error("Validation failed: {}", .0.join("; "))]
+    ValidationError(Vec<String>),
+    #[error("Sheet \"{0}\" already exists")]
+    SheetAlreadyExists(String),
+    #[error("Sheet \"{0}\" has merged cells at {1}")]
+    MergedCells(String, String),
+    #[error("Spreadsheet writing error: {0}")]
+    WriteError(String),
+    #[error("Invalid number: {0}")]
+    ParseFloatError(#[from] std::num::ParseFloatError),
+    #[error("{0}")]
+    Context(String, #[source] Box<ExcelDbError>),
+    #[error("Could not decrypt workbook: {0}")]
+    EncryptionError(String),
+    #[cfg(feature = "xls")]
+    #[error("XLS reading error: {0}")]
+    XlsError(#[from] calamine::Error),
+    #[error("Unsupported file format: {0}")]
+    UnsupportedFormat(String),
+    #[error("Database has no file; it was created with from_rows")]
+    NoFilePath,
+    #[error("Sheet \"{sheet}\" has several columns named \"{column}\" (at {positions:?})")]
+    DuplicateHeader {
+        sheet: String,
+        column: String,
+        positions: Vec<usize>,
+    },
+    #[error("Named range \"{0}\" not found")]
+    NamedRangeNotFound(String),
+    #[error("In-memory workbook is unusable: a panic occurred while it was locked")]
+    Poisoned,
+}
+
+impl ExcelDbError {
+    /// Wrap the error with a description of what was being done, e.g. `"loading sales.xlsx"`.
+    /// The description is the new error's message and the original error is its
+    /// `std::error::Error::source`, so reporters that walk the chain show each message once.
+    pub fn with_context<S: Into<String>>(self, ctx: S) -> Self {
+        ExcelDbError::Context(ctx.into(), Box::new(self))
+    }
+}
+
+/// Read the workbook at `path`, decrypting it with `password` if one is given. With a
+/// password, any failure is reported as `ExcelDbError::EncryptionError`.
+fn read_xlsx(path: &Path, password: Option<&str>) -> Result<Spreadsheet, ExcelDbError> {
+    match password {
+        Some(pwd) => reader::xlsx::read_with_password(path, pwd)
+            .map_err(|e| ExcelDbError::EncryptionError(e.to_string())),
+        None => Ok(reader::xlsx::read(path)?),
+    }
+}
+
+/// Lock a shared in-memory workbook.
+///
+/// # Errors
+///
+/// `Poisoned` if a panic occurred while it was locked, which may have left it half-edited.
+fn lock_book(book: &Mutex<Spreadsheet>) -> Result<MutexGuard<'_, Spreadsheet>, ExcelDbError> {
+    book.lock().map_err(|_| ExcelDbError::Poisoned)
+}
+
+/// An in-memory workbook that the sheets of a `Workbook` share. Cloning it copies the
+/// workbook, so a cloned `ExcelDatabase` never edits the original's.
+#[derive(Debug)]
+struct SharedBook(Arc<Mutex<Spreadsheet>>);
+
+impl SharedBook {
+    fn new(book: Spreadsheet) -> Self {
+        SharedBook(Arc::new(Mutex::new(book)))
+    }
+}
+
+impl Clone for SharedBook {
+    fn clone(&self) -> Self {
+        let book = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        SharedBook::new(book.clone())
+    }
+}
+
+impl std::ops::Deref for SharedBook {
+    type Target = Mutex<Spreadsheet>;
+
+    fn deref(&self) -> &Mutex<Spreadsheet> {
+        &self.0
+    }
+}
+
+/// Write `book` to `path`, encrypted with `password` if one is given, reporting failures
+/// as `ExcelDbError::WriteError`.
+fn write_xlsx(book: &Spreadsheet, path: &Path, password: Option<&str>) -> Result<(), ExcelDbError> {
+    match password {
+        Some(pwd) => writer::xlsx::write_with_password(book, path, pwd),
+        None => writer::xlsx::write(book, path),
+    }
+    .map_err(|e| ExcelDbError::WriteError(e.to_string()))
+}
+
+/// Write the file at `path` with `write`.
+///
+/// If `atomic`, `write` writes `<path>.tmp` in the same directory, which is flushed to disk
+/// and then renamed over the original, so an interrupted write or a crash never leaves a
+/// half-written file. If the rename
+/// fails because the two paths are on different devices, the temporary file is copied over
+/// the original and removed instead (logged as a warning).
+fn write_file<F>(path: &Path, atomic: bool, write: F) -> Result<(), ExcelDbError>
+where
+    F: FnOnce(&Path) -> Result<(), ExcelDbError>,
+{
+    if !atomic {
+        return write(path);
+    }
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+    let written = write(&tmp_path).and_then(|()| {
+        // Opened for writing, as syncing needs write access on some platforms
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&tmp_path)?
+            .sync_all()?;
+        Ok(())
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    match fs::rename(&tmp_path, path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            log::warn!(
+                "rename of {} is not atomic across devices, falling back to copy",
+                tmp_path.display()
+            );
+            let copied = fs::copy(&tmp_path, path);
+            let _ = fs::remove_file(&tmp_path);
+            copied?;
+            Ok(())
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(e.into())
+        }
+    }
+}
+
+/// An in-memory representation of an Excel sheet, providing CRUD-like operations.
+///
+/// Cloning produces an independent copy of the loaded rows and of the in-memory workbook
+/// that still points at the same file and sheet, which is handy for trying out
+/// transformations on a scratch copy. Saving either one writes its whole workbook over the
+/// file.
+#[derive(Debug, Clone)]
+pub struct ExcelDatabase {
+    file_path: String,
+    sheet_name: String,
+    data: Vec<Row>,
+    /// Column names in sheet order (left to right).
+    headers: Vec<String>,
+    read_only: bool,
+    auto_timestamp_column: Option<String>,
+    /// 1-based sheet row holding the (first) header row; rows above it are left untouched.
+    header_row: u32,
+    header_rows: u32,
+    header_style: Option<HeaderStyle>,
+    /// Per `(row index, column)`, formatting applied to that data cell on save.
+    cell_styles: HashMap<(usize, String), CellStyle>,
+    /// Per column, the width (in characters) written on save (see `set_column_width`).
+    column_widths: HashMap<String, f64>,
+    auto_increment: Option<AutoIncrement>,
+    /// Headers and rows as they were at `begin`; `Some` while a transaction is open.
+    transaction_snapshot: Option<(Vec<String>, Vec<Row>)>,
+    /// Per indexed column, the row positions holding each value (see `build_index`).
+    indices: HashMap<String, HashMap<CellValue, Vec<usize>>>,
+    /// Set when rows were handed out mutably (see `rows_mut`); stale indices are not used
+    /// until the next `rebuild_indices`.
+    indices_stale: bool,
+    schema: Option<Schema>,
+    /// Per column, the predicate its values must satisfy (see `add_column_validator`), in
+    /// the order they were added so violations are always reported in the same order.
+    validators: IndexMap<String, ColumnValidator>,
+    /// Write saves to a temporary file and rename it over the original (see
+    /// `ExcelDatabaseBuilder::atomic_save`).
+    atomic_save: bool,
+    /// Write the file after every change; when off, only `commit` and `save` write it (see
+    /// `set_auto_save`).
+    auto_save: bool,
+    save_format: SaveFormat,
+    case_sensitivity: CaseSensitivity,
+    /// Alternative column names, each mapped to its canonical name (see `add_column_alias`).
+    aliases: HashMap<String, String>,
+    /// Directory to copy the file into before every save (see `set_auto_backup`).
+    auto_backup: Option<PathBuf>,
+    merged_cells: MergedCellHandling,
+    empty_cells: EmptyCellStrategy,
+    duplicate_headers: DuplicateHeaderStrategy,
+    /// Changes since the last save; `Some` while change tracking is on.
+    change_log: Option<Vec<Change>>,
+    /// Whether the rows or columns changed since they were last read or written (see
+    /// `has_unsaved_changes`).
+    unsaved: bool,
+    /// Password the workbook is decrypted with (see `ExcelDatabaseBuilder::password`).
+    password: Option<String>,
+    /// Password saves encrypt with, if different from `password`.
+    write_password: Option<String>,
+    /// The workbook as last loaded, kept in memory so saves and sheet operations never
+    /// re-read the file. Shared with the other sheets of a `Workbook` (clones get their own
+    /// copy); `None` for CSV / JSON files and `from_rows`.
+    book: Option<SharedBook>,
+}
+
+impl ExcelDatabase {
+    /// Create a new ExcelDatabase by loading data from the given file path and sheet name.
     ///
-    /// Propagates any I/O or spreadsheet errors from `save_data`.
-    pub fn add_column(
-        &mut self,
-        column_name: &str,
-        default_value: Option<CellValue>,
-    ) -> Result<(), ExcelDbError> {
-        let default_val = default_value.unwrap_or(CellValue::Text(String::new()));
-        for row in self.data.iter_mut() {
-            row.entry(column_name.to_string())
-                .or_insert_with(|| default_val.clone());
+    /// Only the top-left cell of a merged range holds its value; the other cells read as
+    /// empty. Use `builder` to choose another `MergedCellHandling`.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - Path to the `.xlsx` file (e.g., `"data.xlsx"`).
+    /// * `sheet_name` - The sheet name to use; if `None`, defaults to `"Sheet1"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ExcelDbError::SheetNotFound` if the sheet does not exist,
+    /// `ExcelDbError::NoHeaders` if the sheet is empty (no header row), or
+    /// `ExcelDbError::DuplicateHeader` if two columns share a header name (see
+    /// `ExcelDatabaseBuilder::duplicate_headers` to load such sheets).
+    pub fn new<P: AsRef<Path>>(
+        file_path: P,
+        sheet_name: Option<String>,
+    ) -> Result<Self, ExcelDbError> {
+        let mut builder = Self::builder().file_path(file_path);
+        if let Some(sheet) = sheet_name {
+            builder = builder.sheet_name(&sheet);
         }
-        self.save_data()?;
+        builder.build()
+    }
+
+    /// Like `new`, but for sheets whose header spans several rows (e.g. a group row such as
+    /// `"Q1"` above a metric row such as `"Revenue"`).
+    ///
+    /// The `header_rows` levels are flattened into single names joined by `_`
+    /// (`"Q1_Revenue"`, `"Q1_Profit"`, ...). Blank group cells take the group label to
+    /// their left, which is how merged group headers appear. Data starts on the row after
+    /// the last header row. Saving writes the flattened names as a single header row, which
+    /// later reloads and row numbers (see `get_cell_by_coordinate`) then go by.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if `header_rows` is zero.
+    /// - Same as `new` otherwise.
+    pub fn load_with_multilevel_headers<P: AsRef<Path>>(
+        file_path: P,
+        sheet_name: Option<String>,
+        header_rows: u32,
+    ) -> Result<Self, ExcelDbError> {
+        if header_rows == 0 {
+            return Err(ExcelDbError::InvalidParameter(
+                "header_rows must be at least 1".to_string(),
+            ));
+        }
+        let mut db = Self::open(
+            file_path.as_ref(),
+            sheet_name,
+            1,
+            header_rows,
+            MergedCellHandling::default(),
+            DuplicateHeaderStrategy::default(),
+            None,
+        )?;
+        db.data = db.empty_cells.apply(std::mem::take(&mut db.data));
+        Ok(db)
+    }
+
+    /// Create a brand-new `.xlsx` file at `file_path` whose sheet `sheet_name` (default
+    /// `"Sheet1"`) holds only the header row, and open it with no data rows.
+    ///
+    /// # Errors
+    ///
+    /// - `FileAlreadyExists` if something already exists at `file_path`.
+    /// - `InvalidParameter` if `headers` is empty.
+    /// - Propagates I/O or spreadsheet errors from writing or reopening the workbook.
+    pub fn create<P: AsRef<Path>>(
+        file_path: P,
+        sheet_name: Option<String>,
+        headers: Vec<String>,
+    ) -> Result<Self, ExcelDbError> {
+        let path = file_path.as_ref();
+        if path.exists() {
+            return Err(ExcelDbError::FileAlreadyExists(path.to_path_buf()));
+        }
+        if headers.is_empty() {
+            return Err(ExcelDbError::InvalidParameter(
+                "at least one header is required".to_string(),
+            ));
+        }
+        let sheet = sheet_name.unwrap_or_else(|| "Sheet1".to_string());
+        Self::write_new_sheet(path, &sheet, &headers, &[])?;
+        Self::new(path, Some(sheet))
+    }
+
+    /// Open the sheet `sheet_name` (default `"Sheet1"`) of `file_path`, creating what is
+    /// missing first: a new workbook holding only the `headers` row if there is no file, or
+    /// that header row as the sheet if the workbook lacks the sheet or the sheet is empty.
+    /// An existing sheet with a header row is opened as with `new`, ignoring `headers`.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if something has to be created and `headers` is empty.
+    /// - Propagates I/O or spreadsheet errors from reading, writing or reopening the
+    ///   workbook.
+    pub fn open_or_create<P: AsRef<Path>>(
+        file_path: P,
+        sheet_name: Option<String>,
+        headers: Vec<String>,
+    ) -> Result<Self, ExcelDbError> {
+        let path = file_path.as_ref();
+        if !path.exists() {
+            return Self::create(path, sheet_name, headers);
+        }
+        let sheet = sheet_name.unwrap_or_else(|| "Sheet1".to_string());
+        match Self::new(path, Some(sheet.clone())) {
+            Err(ExcelDbError::SheetNotFound(_) | ExcelDbError::NoHeaders(_)) => {
+                if headers.is_empty() {
+                    return Err(ExcelDbError::InvalidParameter(
+                        "at least one header is required".to_string(),
+                    ));
+                }
+                Self::write_new_sheet(path, &sheet, &headers, &[])?;
+                Self::new(path, Some(sheet))
+            }
+            opened => opened,
+        }
+    }
+
+    /// Number of data rows in `sheet_name` of the workbook at `file_path` whose header is
+    /// row 1, taken from the sheet's highest row, without building any `Row`s. For a header
+    /// elsewhere, configure it on a builder and call `ExcelDatabaseBuilder::row_count_on_disk`.
+    ///
+    /// # Errors
+    ///
+    /// Same as `sheet_metadata`.
+    pub fn row_count_on_disk<P: AsRef<Path>>(
+        file_path: P,
+        sheet_name: &str,
+    ) -> Result<usize, ExcelDbError> {
+        Self::builder()
+            .file_path(file_path)
+            .sheet_name(sheet_name)
+            .row_count_on_disk()
+    }
+
+    /// Row count and header names of `sheet_name` in the workbook at `file_path` whose
+    /// header is row 1, read from the header row only, without building any `Row`s. For a
+    /// header elsewhere, configure it on a builder and call
+    /// `ExcelDatabaseBuilder::sheet_metadata`.
+    ///
+    /// # Errors
+    ///
+    /// - `SheetNotFound(sheet_name)` if the workbook has no such sheet.
+    /// - `NoHeaders` if the sheet is empty.
+    /// - Propagates I/O or spreadsheet errors from reading the workbook.
+    pub fn sheet_metadata<P: AsRef<Path>>(
+        file_path: P,
+        sheet_name: &str,
+    ) -> Result<SheetMetadata, ExcelDbError> {
+        Self::builder()
+            .file_path(file_path)
+            .sheet_name(sheet_name)
+            .sheet_metadata()
+    }
+
+    /// Open `sheet_name` (default `"Sheet1"`) for row-by-row iteration: rows are built one
+    /// at a time as the returned `LazyExcelDatabase` is iterated, never all at once. The
+    /// header is row 1 and the other settings are the defaults; configure them on a builder
+    /// and call `ExcelDatabaseBuilder::open_lazy` instead to change them.
+    ///
+    /// # Errors
+    ///
+    /// Same as `ExcelDatabaseBuilder::open_lazy`.
+    pub fn open_lazy<P: AsRef<Path>>(
+        file_path: P,
+        sheet_name: Option<String>,
+    ) -> Result<LazyExcelDatabase, ExcelDbError> {
+        let mut builder = Self::builder().file_path(file_path);
+        if let Some(sheet) = sheet_name {
+            builder = builder.sheet_name(&sheet);
+        }
+        builder.open_lazy()
+    }
+
+    /// Start configuring a database to open; see `ExcelDatabaseBuilder`.
+    pub fn builder() -> ExcelDatabaseBuilder {
+        ExcelDatabaseBuilder::default()
+    }
+
+    /// Shared constructor: load `sheet_name` (default `"Sheet1"`) with the header starting
+    /// at `header_row` and spanning `header_rows` rows, handling merged cells and duplicate
+    /// header names as given and decrypting with `password` if there is one. Empty cells
+    /// are left as `CellValue::Empty` for the caller's `EmptyCellStrategy` to handle.
+    fn open(
+        file_path: &Path,
+        sheet_name: Option<String>,
+        header_row: u32,
+        header_rows: u32,
+        merged_cells: MergedCellHandling,
+        duplicate_headers: DuplicateHeaderStrategy,
+        password: Option<String>,
+    ) -> Result<Self, ExcelDbError> {
+        let path_str = file_path.to_string_lossy().to_string();
+        let sheet = sheet_name.unwrap_or_else(|| "Sheet1".to_string());
+        let save_format = SaveFormat::from_path(file_path);
+        let book = match save_format {
+            SaveFormat::Xlsx => Some(read_xlsx(file_path, password.as_deref())?),
+            SaveFormat::Csv | SaveFormat::Json => None,
+        };
+        let (headers, data) = Self::load_data(
+            &path_str,
+            book.as_ref(),
+            &sheet,
+            header_row,
+            header_rows,
+            merged_cells,
+            duplicate_headers,
+        )?;
+        let mut db = Self::from_loaded(path_str, sheet, headers, data, header_rows);
+        db.header_row = header_row;
+        db.merged_cells = merged_cells;
+        db.duplicate_headers = duplicate_headers;
+        db.save_format = save_format;
+        db.password = password;
+        db.book = book.map(SharedBook::new);
+        Ok(db)
+    }
+
+    /// Build a database over rows that have already been read from `file_path`.
+    fn from_loaded(
+        file_path: String,
+        sheet_name: String,
+        headers: Vec<String>,
+        data: Vec<Row>,
+        header_rows: u32,
+    ) -> Self {
+        Self {
+            file_path,
+            sheet_name,
+            data,
+            headers,
+            read_only: false,
+            auto_timestamp_column: None,
+            header_row: 1,
+            header_rows,
+            header_style: None,
+            cell_styles: HashMap::new(),
+            column_widths: HashMap::new(),
+            auto_increment: None,
+            transaction_snapshot: None,
+            indices: HashMap::new(),
+            indices_stale: false,
+            schema: None,
+            validators: IndexMap::new(),
+            atomic_save: true,
+            auto_save: true,
+            save_format: SaveFormat::Xlsx,
+            case_sensitivity: CaseSensitivity::Sensitive,
+            aliases: HashMap::new(),
+            auto_backup: None,
+            merged_cells: MergedCellHandling::Ignore,
+            empty_cells: EmptyCellStrategy::default(),
+            duplicate_headers: DuplicateHeaderStrategy::default(),
+            change_log: None,
+            unsaved: false,
+            password: None,
+            write_password: None,
+            book: None,
+        }
+    }
+
+    /// Open an ExcelDatabase that refuses every write operation.
+    ///
+    /// Reads behave exactly as with `new`, but `insert`, `update`, `delete`,
+    /// `add_sheet`, `add_column` and `remove_column` return `ExcelDbError::ReadOnly`
+    /// without touching the in-memory data or the file.
+    ///
+    /// # Errors
+    ///
+    /// Same as `new`.
+    pub fn open_read_only<P: AsRef<Path>>(
+        file_path: P,
+        sheet_name: Option<String>,
+    ) -> Result<Self, ExcelDbError> {
+        let mut db = Self::new(file_path, sheet_name)?;
+        db.read_only = true;
+        Ok(db)
+    }
+
+    /// Save the current sheet (unless read-only, and even with auto-save off), then load
+    /// `sheet_name` in its place.
+    ///
+    /// Column indices and the auto-increment column belong to the previous sheet and are
+    /// dropped. If the new sheet cannot be loaded, this instance stays on the current one.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` while a transaction is open; `commit` or `rollback` first.
+    /// - `SheetNotFound` / `NoHeaders` if `sheet_name` cannot be loaded.
+    /// - Propagates any error from saving the current sheet.
+    pub fn switch_sheet(&mut self, sheet_name: &str) -> Result<(), ExcelDbError> {
+        if self.in_transaction() {
+            return Err(ExcelDbError::InvalidParameter(
+                "cannot switch sheets during a transaction".to_string(),
+            ));
+        }
+        if !self.read_only {
+            self.save_now()?;
+        }
+        let (headers, data) = self.load_sheet(sheet_name)?;
+        self.set_active_sheet(sheet_name, headers, data);
         Ok(())
     }
 
-    /// Remove a column from every row, if it exists, and save back to the file.
+    /// Make the freshly loaded `sheet_name` the active sheet, dropping the previous
+    /// sheet's column indices and auto-increment column.
+    fn set_active_sheet(&mut self, sheet_name: &str, headers: Vec<String>, data: Vec<Row>) {
+        self.sheet_name = sheet_name.to_string();
+        self.headers = headers;
+        self.data = data;
+        self.indices.clear();
+        self.auto_increment = None;
+        self.unsaved = false;
+    }
+
+    /// Name of the sheet this instance is currently working on.
+    pub fn active_sheet_name(&self) -> &str {
+        &self.sheet_name
+    }
+
+    /// Choose how column names given to lookups are matched against the header. With
+    /// `Insensitive`, `select`, `update`, `delete`, `get_column_value` and the other
+    /// column-based methods compare names in lowercase; stored names keep their case.
+    pub fn with_case_sensitivity(mut self, cs: CaseSensitivity) -> Self {
+        self.case_sensitivity = cs;
+        self
+    }
+
+    /// Whether this instance was opened with `open_read_only`.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Start a transaction: until `commit` or `rollback`, changes to this sheet's rows are
+    /// kept in memory only. Calling `begin` while a transaction is already open does nothing.
+    ///
+    /// Auto-increment IDs handed out inside a rolled-back transaction are not reused.
+    pub fn begin(&mut self) {
+        if self.transaction_snapshot.is_none() {
+            self.transaction_snapshot = Some((self.headers.clone(), self.data.clone()));
+        }
+    }
+
+    /// Whether a transaction started with `begin` is open.
+    pub fn in_transaction(&self) -> bool {
+        self.transaction_snapshot.is_some()
+    }
+
+    /// End the current transaction and write all of its changes to the file at once.
+    /// Without an open transaction this simply saves the current rows, which is how changes
+    /// made with auto-save off (see `set_auto_save`) are written.
     ///
     /// # Errors
     ///
-    /// Propagates any I/O or spreadsheet errors from `save_data`.
-    pub fn remove_column(&mut self, column_name: &str) -> Result<(), ExcelDbError> {
-        for row in self.data.iter_mut() {
-            row.remove(column_name);
+    /// Propagates any error from saving. The transaction stays open in that case, so it
+    /// can be retried or rolled back.
+    pub fn commit(&mut self) -> Result<(), ExcelDbError> {
+        let snapshot = self.transaction_snapshot.take();
+        if let Err(e) = self.save_now() {
+            self.transaction_snapshot = snapshot;
+            return Err(e);
         }
-        self.save_data()?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// End the current transaction and restore the rows and columns as they were at `begin`.
+    /// Does nothing if no transaction is open.
+    pub fn rollback(&mut self) {
+        if let Some((headers, data)) = self.transaction_snapshot.take() {
+            self.headers = headers;
+            self.data = data;
+            self.rebuild_indices();
+            if let Some(log) = &mut self.change_log {
+                log.clear();
+            }
+        }
+    }
+
+    /// Turn automatic saving on or off (on by default, off for `from_rows`).
+    ///
+    /// With auto-save off, `insert`, `update`, `delete` and every other method that
+    /// normally saves right away only change the rows in memory, so a batch of changes
+    /// costs a single write: call `commit` (or `save`) to flush them. Changes that are
+    /// never committed are lost. Sheet-level operations such as `add_sheet` and
+    /// `freeze_rows` still write the file immediately. Turning auto-save back on does
+    /// not save by itself.
+    pub fn set_auto_save(&mut self, enabled: bool) {
+        self.auto_save = enabled;
+    }
+
+    /// Whether changes are written to the file as soon as they are made.
+    pub fn is_auto_save(&self) -> bool {
+        self.auto_save
+    }
+
+    /// Whether the rows or columns changed in memory since they were last loaded or
+    /// written, e.g. with auto-save off, inside a transaction, or through `get_row_mut`.
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.unsaved
+    }
+
+    /// Start recording every row that methods such as `insert`, `update`, `delete`,
+    /// `upsert`, `find_replace` or `deduplicate` add, change or remove as a `Change`, until
+    /// the next successful save. Since those calls save immediately outside a transaction,
+    /// this is mostly useful between `begin` and `commit`. Edits made through `iter_mut`,
+    /// `get_row_mut` and `filter_mut` are not recorded.
+    pub fn enable_change_tracking(&mut self) {
+        self.change_log.get_or_insert_with(Vec::new);
+    }
+
+    /// The changes recorded since the last save, or `None` if change tracking is off.
+    pub fn pending_changes(&self) -> Option<&[Change]> {
+        self.change_log.as_deref()
+    }
+
+    /// Forget the recorded changes; tracking stays on if it was enabled.
+    pub fn clear_change_log(&mut self) {
+        if let Some(log) = &mut self.change_log {
+            log.clear();
+        }
+    }
+
+    /// Return `ExcelDbError::ReadOnly` if writes are disabled on this instance.
+    fn ensure_writable(&self) -> Result<(), ExcelDbError> {
+        if self.read_only {
+            return Err(ExcelDbError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Parse a fixed-width text file into the sheet `sheet_name` (default `"Sheet1"`) of
+    /// `xlsx_path` and open it.
+    ///
+    /// Each column is `(name, start_byte, end_byte)`, zero-based and inclusive. Every
+    /// non-empty line is sliced at those byte positions (clamped to the line length) and
+    /// trimmed; values are stored as `CellValue::Text`. If `xlsx_path` already exists the
+    /// sheet is replaced (or added), otherwise a new workbook is created.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if `columns` is empty or a column has `start_byte > end_byte`.
+    /// - Propagates I/O or spreadsheet errors from reading the text file or writing the workbook.
+    pub fn import_from_fixed_width<P: AsRef<Path>>(
+        text_path: P,
+        xlsx_path: P,
+        columns: &[(&str, usize, usize)],
+        sheet_name: Option<String>,
+    ) -> Result<Self, ExcelDbError> {
+        if columns.is_empty() {
+            return Err(ExcelDbError::InvalidParameter(
+                "at least one column definition is required".to_string(),
+            ));
+        }
+        if let Some((name, start, end)) = columns.iter().find(|(_, start, end)| start > end) {
+            return Err(ExcelDbError::InvalidParameter(format!(
+                "column \"{}\" starts at byte {} but ends at byte {}",
+                name, start, end
+            )));
+        }
+
+        let content = fs::read_to_string(text_path)?;
+        let rows: Vec<Row> = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let bytes = line.as_bytes();
+                columns
+                    .iter()
+                    .map(|&(name, start, end)| {
+                        let value = if start >= bytes.len() {
+                            String::new()
+                        } else {
+                            let end = end.min(bytes.len() - 1);
+                            String::from_utf8_lossy(&bytes[start..=end])
+                                .trim()
+                                .to_string()
+                        };
+                        (name.to_string(), CellValue::Text(value))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let sheet = sheet_name.unwrap_or_else(|| "Sheet1".to_string());
+        let headers: Vec<String> = columns
+            .iter()
+            .map(|(name, _, _)| name.to_string())
+            .collect();
+        Self::write_new_sheet(xlsx_path.as_ref(), &sheet, &headers, &rows)?;
+        Self::new(xlsx_path, Some(sheet))
+    }
+
+    /// Parse CSV (first record is the header row) into the sheet `sheet_name` of
+    /// `file_path` and open it. All values are stored as `CellValue::Text`. If the file
+    /// already exists the sheet is replaced (or added), otherwise a new workbook is created.
+    ///
+    /// # Errors
+    ///
+    /// - `CsvError` if the input is not valid CSV or its records differ in length.
+    /// - Propagates I/O or spreadsheet errors from writing or reopening the workbook.
+    pub fn from_csv<R: std::io::Read, P: AsRef<Path>>(
+        reader: R,
+        file_path: P,
+        sheet_name: &str,
+    ) -> Result<Self, ExcelDbError> {
+        let mut csv_reader = csv::Reader::from_reader(reader);
+        let headers: Vec<String> = csv_reader.headers()?.iter().map(str::to_string).collect();
+        let mut rows: Vec<Row> = Vec::new();
+        for record in csv_reader.records() {
+            let record = record?;
+            rows.push(
+                headers
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(h, value)| (h.clone(), CellValue::Text(value.to_string())))
+                    .collect(),
+            );
+        }
+        Self::write_new_sheet(file_path.as_ref(), sheet_name, &headers, &rows)?;
+        Self::new(file_path, Some(sheet_name.to_string()))
+    }
+
+    /// Open the sheet `sheet_name` (default `"Sheet1"`) of a legacy `.xls` or `.xlsb`
+    /// workbook. Headers and rows are read exactly as `new` would read them from `.xlsx`.
+    ///
+    /// Writes always produce `.xlsx`: the database points at `<stem>.xlsx` next to the
+    /// original file, which the first save creates. The original file is never modified.
+    /// A `.xlsx` path is accepted too and opened as with `new`. Needs the `xls` feature.
+    ///
+    /// # Errors
+    ///
+    /// - `UnsupportedFormat` if the extension is not `.xls`, `.xlsb` or `.xlsx`.
+    /// - `FileAlreadyExists` if `<stem>.xlsx` already exists, so saves never overwrite it;
+    ///   open that file with `new` instead.
+    /// - `SheetNotFound(sheet_name)` if the workbook has no such sheet.
+    /// - `NoHeaders(sheet_name)` if the sheet is empty.
+    /// - `DuplicateHeader` if two columns share a header name.
+    /// - `XlsError` if the workbook cannot be read.
+    #[cfg(feature = "xls")]
+    pub fn from_xls<P: AsRef<Path>>(
+        file_path: P,
+        sheet_name: Option<String>,
+    ) -> Result<Self, ExcelDbError> {
+        let path = file_path.as_ref();
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "xlsx" => return Self::new(path, sheet_name),
+            "xls" | "xlsb" => {}
+            _ => return Err(ExcelDbError::UnsupportedFormat(path.display().to_string())),
+        }
+
+        let xlsx_path = path.with_extension("xlsx");
+        if xlsx_path.exists() {
+            return Err(ExcelDbError::FileAlreadyExists(xlsx_path));
+        }
+        let sheet = sheet_name.unwrap_or_else(|| "Sheet1".to_string());
+        let mut workbook = calamine::open_workbook_auto(path)?;
+        if !workbook.sheet_names().contains(&sheet) {
+            return Err(ExcelDbError::SheetNotFound(sheet));
+        }
+        let range = workbook.worksheet_range(&sheet)?;
+        // The range starts at the first non-empty cell; pad it back to absolute positions
+        // so `header_row` and the columns mean the same as for `.xlsx`
+        let (first_row, first_col) = range.start().unwrap_or((0, 0));
+        let empty = CellValue::from_calamine(&calamine::Data::Empty);
+        let mut rows: Vec<Vec<CellValue>> = vec![Vec::new(); first_row as usize];
+        rows.extend(range.rows().map(|row| {
+            let mut cells = vec![empty.clone(); first_col as usize];
+            cells.extend(row.iter().map(CellValue::from_calamine));
+            cells
+        }));
+        let (headers, data) =
+            Self::rows_to_table(rows, &sheet, 1, 1, DuplicateHeaderStrategy::default())?;
+        let data = EmptyCellStrategy::default().apply(data);
+        let mut book = umya_spreadsheet::new_file_empty_worksheet();
+        book.add_worksheet(&sheet, Self::build_worksheet(&headers, &data, None, 1));
+        let xlsx_path = xlsx_path.to_string_lossy().to_string();
+        let mut db = Self::from_loaded(xlsx_path, sheet, headers, data, 1);
+        db.book = Some(SharedBook::new(book));
+        Ok(db)
+    }
+
+    /// An in-memory database over `rows` with the columns `headers`, backed by no file
+    /// (`file_path` is empty, the sheet is `"Sheet1"`). Handy for unit tests.
+    ///
+    /// Auto-save starts off (see `set_auto_save`), so inserts, updates and deletes only
+    /// change the rows in memory. `save`, `commit`, and anything else that reads or writes
+    /// the workbook, fail with `ExcelDbError::NoFilePath`, as do changes made after
+    /// turning auto-save back on.
+    pub fn from_rows(rows: Vec<Row>, headers: Vec<String>) -> Self {
+        let mut db = Self::from_loaded(String::new(), "Sheet1".to_string(), headers, rows, 1);
+        db.auto_save = false;
+        db
+    }
+
+    /// Write `headers` and `rows` as the sheet `sheet` of `xlsx_path`, replacing that sheet
+    /// if the workbook exists or creating a new workbook otherwise.
+    fn write_new_sheet(
+        xlsx_path: &Path,
+        sheet: &str,
+        headers: &[String],
+        rows: &[Row],
+    ) -> Result<(), ExcelDbError> {
+        let mut book = if xlsx_path.exists() {
+            let mut book = reader::xlsx::read(xlsx_path)?;
+            book.remove_sheet_by_name(sheet);
+            book
+        } else {
+            umya_spreadsheet::new_file_empty_worksheet()
+        };
+        book.add_worksheet(sheet, Self::build_worksheet(headers, rows, None, 1));
+        write_xlsx(&book, xlsx_path, None)?;
+        Ok(())
+    }
+
+    /// Load the header names and all rows from the given sheet of `book` (or of the `.csv` /
+    /// `.json` file at `file_path`) into memory.
+    ///
+    /// The `header_rows` rows starting at the 1-based `header_row` are treated as the header
+    /// (column names); rows above them are skipped. A `.csv` or `.json` file is read as a
+    /// single table whatever `sheet_name` is; a JSON file's columns are the keys of its rows.
+    /// With more than one header row, the levels are joined with `_` into a flat name.
+    /// Merged cells are handled as `merged_cells` says.
+    ///
+    /// # Errors
+    ///
+    /// - `NoFilePath` if `book` is `None` and `file_path` is not a `.csv` / `.json` file.
+    /// - `CsvError` / `JsonError` if a `.csv` / `.json` file cannot be parsed.
+    /// - `SheetNotFound(sheet_name)` if the sheet is not found.
+    /// - `NoHeaders(sheet_name)` if the sheet ends before the last header row.
+    /// - `MergedCells` if the sheet has merged cells and `merged_cells` is `Error`.
+    /// - `DuplicateHeader` if two columns share a name and `duplicate_headers` is `Error`.
+    fn load_data(
+        file_path: &str,
+        book: Option<&Spreadsheet>,
+        sheet_name: &str,
+        header_row: u32,
+        header_rows: u32,
+        merged_cells: MergedCellHandling,
+        duplicate_headers: DuplicateHeaderStrategy,
+    ) -> Result<(Vec<String>, Vec<Row>), ExcelDbError> {
+        // CSV and JSON files (see `SaveFormat`) hold a single table
+        match SaveFormat::from_path(Path::new(file_path)) {
+            SaveFormat::Csv => {
+                let mut reader = csv::ReaderBuilder::new()
+                    .has_headers(false)
+                    .flexible(true)
+                    .from_path(file_path)?;
+                let mut rows: Vec<Vec<CellValue>> = Vec::new();
+                for record in reader.records() {
+                    rows.push(
+                        record?
+                            .iter()
+                            .map(|f| match f {
+                                "" => CellValue::Empty,
+                                f => CellValue::Text(f.to_string()),
+                            })
+                            .collect(),
+                    );
+                }
+                return Self::rows_to_table(
+                    rows,
+                    sheet_name,
+                    header_row,
+                    header_rows,
+                    duplicate_headers,
+                );
+            }
+            SaveFormat::Json => {
+                let data: Vec<Row> = serde_json::from_str(&fs::read_to_string(file_path)?)
+                    .map_err(|e| ExcelDbError::JsonError(e.to_string()))?;
+                return Ok((Self::collect_headers(&data), data));
+            }
+            SaveFormat::Xlsx => {}
+        }
+
+        let book = book.ok_or(ExcelDbError::NoFilePath)?;
+        if !book.has_sheet(sheet_name) {
+            return Err(ExcelDbError::SheetNotFound(sheet_name.to_string()));
+        }
+        let worksheet = book.get_sheet_by_name(sheet_name).unwrap();
+        Self::read_worksheet(
+            worksheet,
+            sheet_name,
+            header_row,
+            header_rows,
+            merged_cells,
+            duplicate_headers,
+        )
+    }
+
+    /// `load_data` for `sheet_name` of this database's in-memory workbook (or its `.csv` /
+    /// `.json` file), with its header, merged cell, duplicate header, empty cell and column
+    /// alias settings.
+    fn load_sheet(&self, sheet_name: &str) -> Result<(Vec<String>, Vec<Row>), ExcelDbError> {
+        let book = self.book.as_deref().map(lock_book).transpose()?;
+        let (mut headers, mut data) = Self::load_data(
+            &self.file_path,
+            book.as_deref(),
+            sheet_name,
+            self.header_row,
+            self.header_rows,
+            self.merged_cells,
+            self.duplicate_headers,
+        )?;
+        self.apply_aliases(&mut headers, &mut data);
+        Ok((headers, self.empty_cells.apply(data)))
+    }
+
+    /// Rename the columns of freshly loaded `headers` / `data` that are registered aliases
+    /// to their canonical names, unless the canonical name is already a column.
+    fn apply_aliases(&self, headers: &mut [String], data: &mut [Row]) {
+        for pos in 0..headers.len() {
+            let Some(canonical) = self.aliases.get(&headers[pos]) else {
+                continue;
+            };
+            if headers.contains(canonical) {
+                continue;
+            }
+            let alias = std::mem::replace(&mut headers[pos], canonical.clone());
+            for row in data.iter_mut() {
+                if let Some((idx, _, value)) = row.shift_remove_full(&alias) {
+                    row.shift_insert(idx, canonical.clone(), value);
+                }
+            }
+        }
+    }
+
+    /// Read a worksheet into its header names and data rows.
+    ///
+    /// Rows above the 1-based `header_row` are skipped. The next `header_rows` rows form the
+    /// header (see `flatten_headers`), with repeated names handled as `duplicate_headers`
+    /// says; every later row becomes a `Row`, with missing cells read as `CellValue::Empty`.
+    ///
+    /// # Errors
+    ///
+    /// - `NoHeaders(sheet_name)` if the sheet ends before the last header row.
+    /// - `MergedCells` if the sheet has merged cells and `merged_cells` is `Error`.
+    /// - `DuplicateHeader` if two columns share a name and `duplicate_headers` is `Error`.
+    fn read_worksheet(
+        worksheet: &Worksheet,
+        sheet_name: &str,
+        header_row: u32,
+        header_rows: u32,
+        merged_cells: MergedCellHandling,
+        duplicate_headers: DuplicateHeaderStrategy,
+    ) -> Result<(Vec<String>, Vec<Row>), ExcelDbError> {
+        // Collect each row as Vec<CellValue>, by position: missing rows and cells are `Empty`
+        let mut rows: Vec<Vec<CellValue>> = Vec::new();
+        for row in worksheet.get_row_iter() {
+            let mut row_vals: Vec<CellValue> = Vec::new();
+            for cell in row.get_cell_iter() {
+                let col_idx = (*cell.get_coordinate().get_col_num() as usize).saturating_sub(1);
+                if row_vals.len() < col_idx {
+                    row_vals.resize(col_idx, CellValue::Empty);
+                }
+                row_vals.push(CellValue::from_cell(cell));
+            }
+            let row_idx = (*row.get_row_num() as usize).saturating_sub(1);
+            if rows.len() < row_idx {
+                rows.resize(row_idx, Vec::new());
+            }
+            rows.push(row_vals);
+        }
+        Self::apply_merged_cells(worksheet, sheet_name, &mut rows, merged_cells)?;
+        Self::rows_to_table(rows, sheet_name, header_row, header_rows, duplicate_headers)
+    }
+
+    /// Split raw cell rows into header names and data rows, as described for
+    /// `read_worksheet`.
+    fn rows_to_table(
+        mut rows: Vec<Vec<CellValue>>,
+        sheet_name: &str,
+        header_row: u32,
+        header_rows: u32,
+        duplicate_headers: DuplicateHeaderStrategy,
+    ) -> Result<(Vec<String>, Vec<Row>), ExcelDbError> {
+        // Skip any title block above the header
+        let skipped = (header_row.saturating_sub(1) as usize).min(rows.len());
+        rows.drain(..skipped);
+
+        // If there are not enough rows, we cannot infer headers
+        let header_rows = header_rows as usize;
+        if rows.len() < header_rows {
+            return Err(ExcelDbError::NoHeaders(sheet_name.to_string()));
+        }
+
+        // The first row(s) are interpreted as header names
+        let names = Self::flatten_headers(&rows[..header_rows]);
+        let columns = Self::resolve_duplicate_headers(names, sheet_name, duplicate_headers)?;
+
+        // Convert subsequent rows into Row maps
+        let mut data: Vec<Row> = Vec::new();
+        for row_vals in rows.into_iter().skip(header_rows) {
+            let mut row_map: Row = IndexMap::new();
+            for (col_idx, header) in columns.iter() {
+                let value = row_vals.get(*col_idx).cloned().unwrap_or(CellValue::Empty);
+                row_map.insert(header.clone(), value);
+            }
+            data.push(row_map);
+        }
+
+        let headers = columns.into_iter().map(|(_, header)| header).collect();
+        Ok((headers, data))
+    }
+
+    /// The `(zero-based column position, header)` pairs of `worksheet`, read from the
+    /// `header_rows` rows starting at the 1-based `header_row` and named as loading names
+    /// them, without reading any data rows. `merged` are the ranges whose top-left value
+    /// fills the whole range (see `merged_ranges`).
+    ///
+    /// # Errors
+    ///
+    /// - `NoHeaders` if the sheet ends before the last header row.
+    /// - `DuplicateHeader` as for `resolve_duplicate_headers`.
+    fn read_header_columns(
+        worksheet: &Worksheet,
+        sheet_name: &str,
+        header_row: u32,
+        header_rows: u32,
+        duplicate_headers: DuplicateHeaderStrategy,
+        merged: &[CellRange],
+    ) -> Result<Vec<(usize, String)>, ExcelDbError> {
+        if worksheet.get_highest_row() < header_row + header_rows - 1 {
+            return Err(ExcelDbError::NoHeaders(sheet_name.to_string()));
+        }
+        let levels: Vec<Vec<CellValue>> = (header_row..header_row + header_rows)
+            .map(|row| {
+                let width = (1..=worksheet.get_highest_column())
+                    .rev()
+                    .find(|&col| {
+                        worksheet.get_cell(col, row).is_some()
+                            || !Self::read_cell(worksheet, merged, col, row).is_empty()
+                    })
+                    .unwrap_or(0);
+                (1..=width)
+                    .map(|col| Self::read_cell(worksheet, merged, col, row))
+                    .collect()
+            })
+            .collect();
+        let names = Self::flatten_headers(&levels);
+        Self::resolve_duplicate_headers(names, sheet_name, duplicate_headers)
+    }
+
+    /// The `(zero-based column position, header)` pairs to load from header row `names`,
+    /// with blank names replaced by `Column_<n>` and repeated names handled as `strategy`
+    /// says.
+    ///
+    /// # Errors
+    ///
+    /// `DuplicateHeader` for the first repeated name if `strategy` is `Error`.
+    fn resolve_duplicate_headers(
+        names: Vec<String>,
+        sheet_name: &str,
+        strategy: DuplicateHeaderStrategy,
+    ) -> Result<Vec<(usize, String)>, ExcelDbError> {
+        let names: Vec<String> = names
+            .into_iter()
+            .enumerate()
+            .map(|(col_idx, name)| {
+                if name.trim().is_empty() {
+                    format!("Column_{}", col_idx + 1)
+                } else {
+                    name
+                }
+            })
+            .collect();
+        let mut positions: IndexMap<&str, Vec<usize>> = IndexMap::new();
+        for (col_idx, name) in names.iter().enumerate() {
+            positions.entry(name.as_str()).or_default().push(col_idx);
+        }
+        let keep: HashSet<usize> = match strategy {
+            DuplicateHeaderStrategy::Error => {
+                if let Some((name, at)) = positions.iter().find(|(_, at)| at.len() > 1) {
+                    return Err(ExcelDbError::DuplicateHeader {
+                        sheet: sheet_name.to_string(),
+                        column: name.to_string(),
+                        positions: at.clone(),
+                    });
+                }
+                (0..names.len()).collect()
+            }
+            DuplicateHeaderStrategy::RenameWithSuffix => (0..names.len()).collect(),
+            DuplicateHeaderStrategy::KeepFirst => positions.values().map(|at| at[0]).collect(),
+            DuplicateHeaderStrategy::KeepLast => {
+                positions.values().map(|at| at[at.len() - 1]).collect()
+            }
+        };
+
+        let mut taken: HashSet<String> = HashSet::new();
+        let mut columns = Vec::with_capacity(keep.len());
+        for (col_idx, name) in names.iter().enumerate() {
+            if !keep.contains(&col_idx) {
+                continue;
+            }
+            let mut header = name.clone();
+            let mut suffix = 2;
+            while taken.contains(&header) {
+                header = format!("{}_{}", name, suffix);
+                suffix += 1;
+            }
+            taken.insert(header.clone());
+            columns.push((col_idx, header));
+        }
+        Ok(columns)
+    }
+
+    /// The merged ranges of `worksheet` whose top-left value should fill the whole range when
+    /// cells are read one at a time with `read_cell`: all of them for `Propagate`, none for
+    /// `Ignore`.
+    ///
+    /// # Errors
+    ///
+    /// `MergedCells` for `MergedCellHandling::Error` if the sheet has any merged range.
+    fn merged_ranges(
+        worksheet: &Worksheet,
+        sheet_name: &str,
+        handling: MergedCellHandling,
+    ) -> Result<Vec<CellRange>, ExcelDbError> {
+        match handling {
+            MergedCellHandling::Ignore => Ok(Vec::new()),
+            MergedCellHandling::Error => {
+                Self::apply_merged_cells(worksheet, sheet_name, &mut Vec::new(), handling)?;
+                Ok(Vec::new())
+            }
+            MergedCellHandling::Propagate => Ok(worksheet
+                .get_merge_cells()
+                .iter()
+                .filter_map(|range| Self::parse_range(&range.get_range()))
+                .collect()),
+        }
+    }
+
+    /// The value of the cell at the 1-based `col` / `row` of `worksheet` (`Empty` if there is
+    /// no cell), or of the top-left cell of the range in `merged` that covers it.
+    fn read_cell(worksheet: &Worksheet, merged: &[CellRange], col: u32, row: u32) -> CellValue {
+        let (c, r) = (col as usize - 1, row as usize - 1);
+        let (col, row) = merged
+            .iter()
+            .find(|((c0, r0), (c1, r1))| (*c0..=*c1).contains(&c) && (*r0..=*r1).contains(&r))
+            .map_or((col, row), |((c0, r0), _)| (*c0 as u32 + 1, *r0 as u32 + 1));
+        worksheet
+            .get_cell(col, row)
+            .map_or(CellValue::Empty, CellValue::from_cell)
+    }
+
+    /// Apply `handling` to the merged ranges of `worksheet`, whose cells were read into `rows`.
+    fn apply_merged_cells(
+        worksheet: &Worksheet,
+        sheet_name: &str,
+        rows: &mut Vec<Vec<CellValue>>,
+        handling: MergedCellHandling,
+    ) -> Result<(), ExcelDbError> {
+        match handling {
+            MergedCellHandling::Ignore => {}
+            MergedCellHandling::Error => {
+                if let Some(range) = worksheet.get_merge_cells().first() {
+                    return Err(ExcelDbError::MergedCells(
+                        sheet_name.to_string(),
+                        range.get_range(),
+                    ));
+                }
+            }
+            MergedCellHandling::Propagate => {
+                for range in worksheet.get_merge_cells() {
+                    let Some(((first_col, first_row), (last_col, last_row))) =
+                        Self::parse_range(&range.get_range())
+                    else {
+                        continue;
+                    };
+                    let Some(master) = rows.get(first_row).and_then(|r| r.get(first_col)).cloned()
+                    else {
+                        continue;
+                    };
+                    if rows.len() <= last_row {
+                        rows.resize(last_row + 1, Vec::new());
+                    }
+                    for row in &mut rows[first_row..=last_row] {
+                        if row.len() <= last_col {
+                            row.resize(last_col + 1, CellValue::Empty);
+                        }
+                        for cell in &mut row[first_col..=last_col] {
+                            *cell = master.clone();
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Zero-based `(column, row)` corners of a range such as `"A1:C2"` (or a single `"B3"`).
+    fn parse_range(range: &str) -> Option<CellRange> {
+        let (start, end) = range.split_once(':').unwrap_or((range, range));
+        let start = Self::parse_cell_ref(start)?;
+        let end = Self::parse_cell_ref(end)?;
+        Some((
+            (start.0.min(end.0), start.1.min(end.1)),
+            (start.0.max(end.0), start.1.max(end.1)),
+        ))
+    }
+
+    /// Zero-based `(column, row)` of a cell reference such as `"B3"` or `"$B$3"`.
+    fn parse_cell_ref(cell: &str) -> Option<(usize, usize)> {
+        let cell: String = cell.trim().chars().filter(|&c| c != '$').collect();
+        let split = cell.find(|c: char| c.is_ascii_digit())?;
+        let col = Self::col_letter_to_index(&cell[..split])?;
+        let row = cell[split..].parse::<usize>().ok()?.checked_sub(1)?;
+        Some((col, row))
+    }
+
+    /// Combine one or more header rows into a single list of column names.
+    ///
+    /// Blank cells in every level but the last inherit the value to their left, so a group
+    /// label such as `"Q1"` spanning several columns (typically a merged cell) applies to
+    /// each of them. Non-empty levels are joined with `_`, e.g. `"Q1_Revenue"`.
+    fn flatten_headers(levels: &[Vec<CellValue>]) -> Vec<String> {
+        let width = levels.iter().map(|level| level.len()).max().unwrap_or(0);
+        let mut filled: Vec<Vec<String>> = Vec::new();
+        for (depth, level) in levels.iter().enumerate() {
+            let is_last = depth + 1 == levels.len();
+            let mut names: Vec<String> = Vec::with_capacity(width);
+            for col_idx in 0..width {
+                let name = level
+                    .get(col_idx)
+                    .map(|cv| cv.to_string().trim().to_string())
+                    .unwrap_or_default();
+                let name = match names.last() {
+                    Some(prev) if name.is_empty() && !is_last => prev.clone(),
+                    _ => name,
+                };
+                names.push(name);
+            }
+            filled.push(names);
+        }
+        (0..width)
+            .map(|col_idx| {
+                filled
+                    .iter()
+                    .map(|names| names[col_idx].as_str())
+                    .filter(|name| !name.is_empty())
+                    .collect::<Vec<&str>>()
+                    .join("_")
+            })
+            .collect()
+    }
+
+    /// Save the current in-memory `data` back into the Excel file, overwriting the sheet.
+    ///
+    /// The header row is always written from the tracked column names, even when there
+    /// are no data rows. While a transaction is open, or with auto-save off, this only
+    /// checks writability; the file is written on `commit`. With `set_auto_backup`, the
+    /// file is backed up first.
+    /// A successful write clears the change log (see `enable_change_tracking`). With a
+    /// `SaveFormat` other than `Xlsx`, the rows are written as a `.csv` / `.json` file
+    /// instead (see `set_save_format`), with the same backup and atomic write.
+    ///
+    /// # Errors
+    ///
+    /// - `ReadOnly` if the database was opened with `open_read_only`.
+    /// - `NoFilePath` if a write is due but the database was created with `from_rows`,
+    ///   which has no file (its auto-save is off, so its changes normally stay in memory).
+    /// - `SheetNotFound(sheet_name)` if the sheet cannot be found when writing.
+    /// - I/O or spreadsheet errors if the backup or the underlying write fails.
+    pub(crate) fn save_data(&mut self) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        self.unsaved = true;
+        self.sync_headers();
+        self.rebuild_indices();
+        if self.transaction_snapshot.is_some() || !self.auto_save {
+            return Ok(());
+        }
+        if self.file_path.is_empty() {
+            return Err(ExcelDbError::NoFilePath);
+        }
+        let path = self.save_path();
+        // Nothing to back up on the first save after `from_xls` or `set_save_format`
+        if let Some(dir) = &self.auto_backup {
+            if path.exists() {
+                self.backup_to(dir)?;
+            }
+        }
+        match self.save_format {
+            SaveFormat::Xlsx => {
+                let mut book = self.workbook()?;
+                self.replace_sheet(&mut book)?;
+                self.write_workbook(&book)?;
+            }
+            SaveFormat::Csv => write_file(&path, self.atomic_save, |path| {
+                Self::write_csv(fs::File::create(path)?, &self.headers, &self.data)
+            })?,
+            SaveFormat::Json => {
+                let json = self.to_json_string()?;
+                write_file(&path, self.atomic_save, |path| Ok(fs::write(path, json)?))?;
+            }
+        }
+        self.finish_write();
+        Ok(())
+    }
+
+    /// Bookkeeping after a successful write: clear the change log and the unsaved flag, and
+    /// read the header as the single row of flattened names that was written (see
+    /// `load_with_multilevel_headers`) from now on.
+    fn finish_write(&mut self) {
+        self.header_rows = 1;
+        self.unsaved = false;
+        self.clear_change_log();
+    }
+
+    /// `save_data`, writing the file even with auto-save off.
+    fn save_now(&mut self) -> Result<(), ExcelDbError> {
+        let auto_save = std::mem::replace(&mut self.auto_save, true);
+        let saved = self.save_data();
+        self.auto_save = auto_save;
+        saved
+    }
+
+    /// The file saves write: the file path, with its extension replaced by `.csv` / `.json`
+    /// for those `SaveFormat`s.
+    fn save_path(&self) -> PathBuf {
+        let path = Path::new(&self.file_path);
+        match self.save_format {
+            SaveFormat::Xlsx => path.to_path_buf(),
+            SaveFormat::Csv => path.with_extension("csv"),
+            SaveFormat::Json => path.with_extension("json"),
+        }
+    }
+
+    /// Copy the file saves write (see `save_path`) into `dir` (created if missing) as
+    /// `<stem>_<UTC timestamp>.<ext>.bak`, e.g. `data_2024-06-15T10-30-00.123.xlsx.bak`, and
+    /// return the copy's path. If that name is taken, a counter is added before the
+    /// extension (`..._2024-06-15T10-30-00.123_1.xlsx.bak`) so no backup is overwritten.
+    fn backup_to(&self, dir: &Path) -> Result<PathBuf, ExcelDbError> {
+        if self.file_path.is_empty() {
+            return Err(ExcelDbError::NoFilePath);
+        }
+        fs::create_dir_all(dir)?;
+        let path = &self.save_path();
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let ext = path.extension().unwrap_or_default().to_string_lossy();
+        let timestamp = Utc::now().format("%Y-%m-%dT%H-%M-%S%.3f").to_string();
+        let mut backup = dir.join(format!("{}_{}.{}.bak", stem, timestamp, ext));
+        let mut counter = 0;
+        while backup.exists() {
+            counter += 1;
+            backup = dir.join(format!("{}_{}_{}.{}.bak", stem, timestamp, counter, ext));
+        }
+        fs::copy(path, &backup)?;
+        Ok(backup)
+    }
+
+    /// Replace this database's sheet in `book` with one rebuilt from the in-memory rows.
+    ///
+    /// # Errors
+    ///
+    /// `SheetNotFound` if `book` has no sheet with this database's sheet name.
+    fn replace_sheet(&self, book: &mut Spreadsheet) -> Result<(), ExcelDbError> {
+        if !book.has_sheet(&self.sheet_name) {
+            return Err(ExcelDbError::SheetNotFound(self.sheet_name.clone()));
+        }
+        let new_ws = self.rebuild_worksheet(book);
+        book.remove_sheet_by_name(&self.sheet_name);
+        book.add_worksheet(&self.sheet_name, new_ws);
+        Ok(())
+    }
+
+    /// A worksheet holding the in-memory header and rows, plus the title block above the
+    /// header row and the frozen panes copied unchanged from this database's sheet in `book`.
+    /// Its auto-filter keeps the same columns (as far as they still exist) but is resized to
+    /// cover the current rows.
+    fn rebuild_worksheet(&self, book: &Spreadsheet) -> Worksheet {
+        let mut new_ws = Self::build_worksheet(
+            &self.headers,
+            &self.data,
+            self.header_style.as_ref(),
+            self.header_row,
+        );
+        if let Some(old_ws) = book.get_sheet_by_name(&self.sheet_name) {
+            for row in 1..self.header_row {
+                for col in 1..=old_ws.get_highest_column() {
+                    if let Some(cell) = old_ws.get_cell(col, row) {
+                        new_ws.add_cell(cell.clone());
+                    }
+                }
+            }
+            if let Some(auto_filter) = old_ws.get_auto_filter() {
+                let old_range = auto_filter.get_range().get_range();
+                if let Some(((first, _), (last, _))) = Self::parse_range(&old_range) {
+                    let last = last.min(self.headers.len().saturating_sub(1));
+                    if first <= last && !self.headers.is_empty() {
+                        new_ws.set_auto_filter(self.auto_filter_range(first + 1, last + 1));
+                    }
+                }
+            }
+            if let Some(pane) = old_ws.get_sheet_view().get_pane() {
+                new_ws.get_sheet_view_mut().set_pane(pane.clone());
+            }
+        }
+        for ((row_index, column), style) in &self.cell_styles {
+            let Some(col_idx) = self.headers.iter().position(|h| h == column) else {
+                continue;
+            };
+            let excel_row = self.header_row + 1 + *row_index as u32;
+            if let Some(cell) = new_ws.get_cell_mut(col_idx as u32 + 1, excel_row) {
+                HeaderStyle::from(*style).apply_to(cell.get_style_mut());
+            }
+        }
+        for (column, width) in &self.column_widths {
+            if let Some(col_idx) = self.headers.iter().position(|h| h == column) {
+                let letter = Self::column_letter(col_idx + 1);
+                new_ws.get_column_dimension_mut(&letter).set_width(*width);
+            }
+        }
+        new_ws
+    }
+
+    /// Read this database's workbook from disk, decrypting it if a password was configured.
+    ///
+    /// # Errors
+    ///
+    /// `NoFilePath` for a database created with `from_rows`; otherwise as `read_xlsx`.
+    fn read_workbook(&self) -> Result<Spreadsheet, ExcelDbError> {
+        if self.file_path.is_empty() {
+            return Err(ExcelDbError::NoFilePath);
+        }
+        read_xlsx(Path::new(&self.file_path), self.password.as_deref())
+    }
+
+    /// Lock this database's in-memory workbook for reading or editing.
+    ///
+    /// # Errors
+    ///
+    /// - `NoFilePath` for a database created with `from_rows`, `UnsupportedFormat` for one
+    ///   opened from a `.csv` / `.json` file, which has no workbook.
+    /// - `Poisoned` if a panic occurred while the workbook was locked.
+    fn workbook(&self) -> Result<MutexGuard<'_, Spreadsheet>, ExcelDbError> {
+        match &self.book {
+            Some(book) => lock_book(book),
+            None if self.file_path.is_empty() => Err(ExcelDbError::NoFilePath),
+            None => Err(ExcelDbError::UnsupportedFormat(self.file_path.clone())),
+        }
+    }
+
+    /// The password saves encrypt with: the write password if set, else the open password,
+    /// so a protected file stays protected.
+    fn save_password(&self) -> Option<&str> {
+        self.write_password.as_deref().or(self.password.as_deref())
+    }
+
+    /// Write `book` to this database's file, atomically unless disabled with
+    /// `ExcelDatabaseBuilder::atomic_save` (see `write_file`).
+    fn write_workbook(&self, book: &Spreadsheet) -> Result<(), ExcelDbError> {
+        write_file(Path::new(&self.file_path), self.atomic_save, |path| {
+            write_xlsx(book, path, self.save_password())
+        })
+    }
+
+    /// Build a worksheet with `headers` in the 1-based row `header_row` and one row per
+    /// entry of `rows` below it.
+    ///
+    /// Cells for columns a row does not have are written as blank cells. If `header_style`
+    /// is given it is applied to the header cells.
+    fn build_worksheet(
+        headers: &[String],
+        rows: &[Row],
+        header_style: Option<&HeaderStyle>,
+        header_row: u32,
+    ) -> Worksheet {
+        let mut ws = Worksheet::new();
+
+        // Write header row
+        for (col_idx, header) in headers.iter().enumerate() {
+            let header_cell = UCellValue::from(header.clone());
+            let mut cell = Cell::new((col_idx + 1) as u32, header_row, header_cell);
+            if let Some(style) = header_style {
+                style.apply_to(cell.get_style_mut());
+            }
+            ws.add_cell(cell);
+        }
+
+        // Write actual data rows starting right below the header
+        for (row_idx, row_map) in rows.iter().enumerate() {
+            let excel_row = header_row + 1 + row_idx as u32;
+            for (col_idx, header) in headers.iter().enumerate() {
+                let value = row_map.get(header).cloned().unwrap_or(CellValue::Empty);
+                ws.add_cell(value.into_cell((col_idx + 1) as u32, excel_row));
+            }
+        }
+        ws
+    }
+
+    /// Re-read the file, replacing the in-memory workbook and rows, to pick up changes made
+    /// to it outside this database. Unsaved changes to the rows are discarded. Clones of this
+    /// database and the other sheets of its `Workbook` share the workbook, so they see the
+    /// reloaded sheets too, but keep their own rows until they are reloaded themselves.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` while a transaction is open; `commit` or `rollback` first.
+    /// - `NoFilePath` for a database created with `from_rows`.
+    /// - `SheetNotFound` / `NoHeaders` if the active sheet can no longer be loaded.
+    /// - Propagates I/O or spreadsheet errors from reading the file.
+    pub fn reload(&mut self) -> Result<(), ExcelDbError> {
+        if self.in_transaction() {
+            return Err(ExcelDbError::InvalidParameter(
+                "cannot reload during a transaction".to_string(),
+            ));
+        }
+        if let Some(book) = &self.book {
+            let fresh = self.read_workbook()?;
+            *lock_book(book)? = fresh;
+        }
+        self.refresh_data()
+    }
+
+    /// Reload the sheet data from the in-memory workbook (or the `.csv` / `.json` file),
+    /// replacing the in-memory `data`.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any errors from `load_data`.
+    fn refresh_data(&mut self) -> Result<(), ExcelDbError> {
+        (self.headers, self.data) = self.load_sheet(&self.sheet_name)?;
+        self.rebuild_indices();
+        self.unsaved = false;
+        Ok(())
+    }
+
+    /// Map each value in `column` to the positions of the rows holding it.
+    fn index_column(rows: &[Row], column: &str) -> HashMap<CellValue, Vec<usize>> {
+        let mut index: HashMap<CellValue, Vec<usize>> = HashMap::new();
+        for (idx, row) in rows.iter().enumerate() {
+            if let Some(value) = row.get(column) {
+                index.entry(value.clone()).or_default().push(idx);
+            }
+        }
+        index
+    }
+
+    /// Recompute every column index from the current rows.
+    fn rebuild_indices(&mut self) {
+        for (column, index) in self.indices.iter_mut() {
+            *index = Self::index_column(&self.data, column);
+        }
+        self.indices_stale = false;
+    }
+
+    /// The rows, for handing out mutably. Callers may change any value, so the column
+    /// indices are marked stale: lookups scan the rows until the indices are rebuilt on
+    /// the next save.
+    fn rows_mut(&mut self) -> &mut Vec<Row> {
+        self.indices_stale = true;
+        self.unsaved = true;
+        &mut self.data
+    }
+
+    /// The index on `column`, unless there is none or it may be stale.
+    fn column_index(&self, column: &str) -> Option<&HashMap<CellValue, Vec<usize>>> {
+        if self.indices_stale {
+            return None;
+        }
+        self.indices.get(column)
+    }
+
+    /// The rows matching `query` (all rows for `None`), using a column index when possible.
+    fn matching_rows<'a>(&'a self, query: Option<&'a Row>) -> Vec<&'a Row> {
+        self.select_iter(query).collect()
+    }
+
+    /// Positions of the rows that can match `query`, taken from the index of the first
+    /// indexed column in the query, or `None` if no query column is indexed.
+    fn indexed_candidates(&self, query: &Row) -> Option<&[usize]> {
+        query.iter().find_map(|(column, value)| {
+            let index = self.column_index(column)?;
+            Some(index.get(value).map_or(&[][..], Vec::as_slice))
+        })
+    }
+
+    /// Append the columns `data` has but `headers` lacks, in the order they are first seen.
+    ///
+    /// Known columns are never dropped here, even if no row has a value for them (as with
+    /// `EmptyCellStrategy::OmitKey`); `remove_column` and the like update `headers` themselves.
+    fn sync_headers(&mut self) {
+        for column in Self::collect_headers(&self.data) {
+            if !self.headers.contains(&column) {
+                self.headers.push(column);
+            }
+        }
+    }
+
+    /// Fill in the auto-timestamp and auto-increment columns of a row about to be inserted,
+    /// leaving any value the caller already supplied in place.
+    fn fill_generated_columns(&mut self, row: &mut Row) {
+        if let Some(column) = &self.auto_timestamp_column {
+            row.entry(column.clone())
+                .or_insert_with(|| CellValue::DateTime(Utc::now().naive_utc()));
+        }
+        if let Some(counter) = self.auto_increment.as_mut() {
+            match row.get(&counter.column).filter(|v| !v.is_empty()) {
+                Some(given) => {
+                    // Keep the sequence ahead of explicitly supplied IDs
+                    if let Some(id) = given.as_f64().filter(|n| *n >= 0.0) {
+                        counter.last_value = counter.last_value.max(id as u64);
+                    }
+                }
+                None => {
+                    counter.last_value += 1;
+                    row.insert(
+                        counter.column.clone(),
+                        CellValue::Number(counter.last_value as f64),
+                    );
+                }
+            }
+        }
+    }
+
+    /// The header name `column` refers to under the current `CaseSensitivity`, or
+    /// `ExcelDbError::ColumnNotFound` if there is none.
+    fn ensure_column(&self, column: &str) -> Result<String, ExcelDbError> {
+        self.column_key(column)
+            .cloned()
+            .ok_or_else(|| ExcelDbError::ColumnNotFound(column.to_string()))
+    }
+
+    /// The header matching `column` (or the canonical name it is an alias of, see
+    /// `add_column_alias`): exactly, or in lowercase with `CaseSensitivity::Insensitive`.
+    fn column_key(&self, column: &str) -> Option<&String> {
+        let column = self.aliases.get(column).map_or(column, String::as_str);
+        match self.case_sensitivity {
+            CaseSensitivity::Sensitive => self.headers.iter().find(|h| *h == column),
+            CaseSensitivity::Insensitive => {
+                let column = column.to_lowercase();
+                self.headers.iter().find(|h| h.to_lowercase() == column)
+            }
+        }
+    }
+
+    /// `query` with each key replaced by the header it refers to (see `column_key`).
+    /// Keys that match no header are kept as given; with `CaseSensitivity::Sensitive` and
+    /// no column aliases `query` is returned as is.
+    fn normalize_query<'a>(&self, query: &'a Row) -> Cow<'a, Row> {
+        match self.case_sensitivity {
+            CaseSensitivity::Sensitive if self.aliases.is_empty() => Cow::Borrowed(query),
+            _ => Cow::Owned(
+                query
+                    .iter()
+                    .map(|(k, v)| (self.column_key(k).unwrap_or(k).clone(), v.clone()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The numeric values in `column`, plus how many non-empty, non-numeric cells were skipped.
+    ///
+    /// Errors with `ColumnNotFound` if no row has the column, or `NotANumericColumn` if it
+    /// has no numeric cell at all.
+    fn numeric_values(&self, column: &str) -> Result<(Vec<f64>, usize), ExcelDbError> {
+        let column = &self.ensure_column(column)?;
+        let mut values = Vec::new();
+        let mut skipped = 0;
+        for value in self.data.iter().filter_map(|row| row.get(column)) {
+            match value.as_f64() {
+                Some(n) => values.push(n),
+                None if !value.is_empty() => skipped += 1,
+                None => {}
+            }
+        }
+        if values.is_empty() {
+            return Err(ExcelDbError::NotANumericColumn(column.to_string()));
+        }
+        Ok((values, skipped))
+    }
+
+    /// Return the first row whose `column` value is ranked highest by `wins`.
+    ///
+    /// Empty cells and values that cannot be compared with the current best are skipped.
+    fn find_extreme_row(&self, column: &str, wins: Ordering) -> Result<Option<Row>, ExcelDbError> {
+        let column = &self.ensure_column(column)?;
+        let mut best: Option<(&Row, &CellValue)> = None;
+        for row in self.data.iter() {
+            let Some(value) = row.get(column).filter(|v| !v.is_empty()) else {
+                continue;
+            };
+            match best {
+                Some((_, current)) if value.compare_loose(current) != Some(wins) => {}
+                _ => best = Some((row, value)),
+            }
+        }
+        Ok(best.map(|(row, _)| row.clone()))
+    }
+
+    /// Stable multi-key sort of `rows`.
+    ///
+    /// A column whose non-empty values all parse as numbers is compared numerically;
+    /// any other column is compared by the values' text. Empty or missing cells sort
+    /// before everything else (after, when descending).
+    fn sort_rows(rows: &mut [Row], columns: &[(&str, SortOrder)]) {
+        let numeric: Vec<bool> = columns
+            .iter()
+            .map(|(column, _)| {
+                rows.iter()
+                    .filter_map(|row| row.get(*column))
+                    .filter(|v| !v.is_empty())
+                    .all(|v| v.as_f64().is_some())
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            for ((column, order), numeric) in columns.iter().zip(numeric.iter()) {
+                let x = a.get(*column).filter(|v| !v.is_empty());
+                let y = b.get(*column).filter(|v| !v.is_empty());
+                let ord = match (x, y) {
+                    (None, None) => Ordering::Equal,
+                    (None, Some(_)) => Ordering::Less,
+                    (Some(_), None) => Ordering::Greater,
+                    (Some(x), Some(y)) if *numeric => x
+                        .as_f64()
+                        .partial_cmp(&y.as_f64())
+                        .unwrap_or(Ordering::Equal),
+                    (Some(x), Some(y)) => x.to_string().cmp(&y.to_string()),
+                };
+                let ord = match order {
+                    SortOrder::Ascending => ord,
+                    SortOrder::Descending => ord.reverse(),
+                };
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            Ordering::Equal
+        });
+    }
+
+    /// For each row, pick the numeric value in the trailing `window` that `wins` every comparison.
+    ///
+    /// Positions before the first full window, and windows with no numeric cell, yield `None`.
+    fn rolling_extreme(
+        &self,
+        column: &str,
+        window: usize,
+        wins: Ordering,
+    ) -> Result<Vec<Option<CellValue>>, ExcelDbError> {
+        let column = &self.ensure_column(column)?;
+        if window == 0 {
+            return Err(ExcelDbError::InvalidParameter(
+                "window must be greater than zero".to_string(),
+            ));
+        }
+        let numbers: Vec<Option<(f64, &CellValue)>> = self
+            .data
+            .iter()
+            .map(|row| row.get(column).and_then(|v| v.as_f64().map(|n| (n, v))))
+            .collect();
+        let result = (0..numbers.len())
+            .map(|i| {
+                if i + 1 < window {
+                    return None;
+                }
+                numbers[i + 1 - window..=i]
+                    .iter()
+                    .flatten()
+                    .fold(
+                        None,
+                        |best: Option<(f64, &CellValue)>, &(n, v)| match best {
+                            Some((b, _)) if n.partial_cmp(&b) != Some(wins) => best,
+                            _ => Some((n, v)),
+                        },
+                    )
+                    .map(|(_, v)| v.clone())
+            })
+            .collect();
+        Ok(result)
+    }
+
+    /// Column names across `rows`, in the order they are first encountered.
+    fn collect_headers(rows: &[Row]) -> Vec<String> {
+        let mut headers: Vec<String> = Vec::new();
+        for row in rows {
+            for key in row.keys() {
+                if !headers.contains(key) {
+                    headers.push(key.clone());
+                }
+            }
+        }
+        headers
+    }
+
+    /// Write `headers` and then one record per row as RFC 4180 CSV.
+    fn write_csv<W: Write>(
+        writer: W,
+        headers: &[String],
+        rows: &[Row],
+    ) -> Result<(), ExcelDbError> {
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        csv_writer.write_record(headers)?;
+        for row in rows {
+            csv_writer.write_record(
+                headers
+                    .iter()
+                    .map(|h| row.get(h).map(|v| v.to_string()).unwrap_or_default()),
+            )?;
+        }
+        csv_writer.flush()?;
+        Ok(())
+    }
+
+    /// Escape backslashes, tabs and line breaks so a value fits in a single TSV field.
+    fn escape_tsv(value: &str) -> String {
+        let mut out = String::with_capacity(value.len());
+        for c in value.chars() {
+            match c {
+                '\\' => out.push_str("\\\\"),
+                '\t' => out.push_str("\\t"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                _ => out.push(c),
+            }
+        }
+        out
+    }
+
+    /// Reverse `escape_tsv`. Unknown escape sequences are kept verbatim.
+    fn unescape_tsv(field: &str) -> String {
+        let mut out = String::with_capacity(field.len());
+        let mut chars = field.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('t') => out.push('\t'),
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('\\') => out.push('\\'),
+                Some(other) => {
+                    out.push('\\');
+                    out.push(other);
+                }
+                None => out.push('\\'),
+            }
+        }
+        out
+    }
+
+    /// Order two cells, falling back to comparing them as date-times when their variants
+    /// differ (e.g. a `DateTime` against date text).
+    fn compare_values(a: &CellValue, b: &CellValue) -> Option<Ordering> {
+        a.compare_loose(b)
+            .or_else(|| match (a.as_datetime(), b.as_datetime()) {
+                (Some(x), Some(y)) => x.partial_cmp(&y),
+                _ => None,
+            })
+    }
+
+    /// Convert a 1-based column number to its Excel letters (`1` -> `"A"`, `27` -> `"AA"`).
+    fn column_letter(mut col: usize) -> String {
+        let mut letters = Vec::new();
+        while col > 0 {
+            let rem = (col - 1) % 26;
+            letters.push((b'A' + rem as u8) as char);
+            col = (col - 1) / 26;
+        }
+        letters.iter().rev().collect()
+    }
+
+    /// Zero-based column index for an Excel column letter such as `"A"` or `"AA"`
+    /// (case-insensitive), or `None` if `s` is not a column letter.
+    fn col_letter_to_index(s: &str) -> Option<usize> {
+        if s.is_empty() || !s.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        let col = s.bytes().try_fold(0usize, |acc, b| {
+            acc.checked_mul(26)?
+                .checked_add((b.to_ascii_uppercase() - b'A' + 1) as usize)
+        })?;
+        Some(col - 1)
+    }
+
+    /// Data row index for a 1-based Excel row number, or `None` for header rows.
+    fn excel_row_to_index(&self, excel_row: u32) -> Option<usize> {
+        (excel_row as usize).checked_sub((self.header_row + self.header_rows) as usize)
+    }
+
+    /// Rows whose `column` text matches `re`, or `None` if there are none.
+    fn select_by_regex(&self, column: &str, re: &Regex) -> Result<Option<Vec<Row>>, ExcelDbError> {
+        self.select_by_text(column, |text| re.is_match(text))
+    }
+
+    /// Rows whose `column` text satisfies `matches`, or `None` if there are none.
+    fn select_by_text<F: Fn(&str) -> bool>(
+        &self,
+        column: &str,
+        matches: F,
+    ) -> Result<Option<Vec<Row>>, ExcelDbError> {
+        let column = &self.ensure_column(column)?;
+        let result: Vec<Row> = self
+            .data
+            .iter()
+            .filter(|row| row.get(column).is_some_and(|v| matches(&v.to_string())))
+            .cloned()
+            .collect();
+        Ok(if result.is_empty() {
+            None
+        } else {
+            Some(result)
+        })
+    }
+
+    /// Whether `row` contains every key-value pair in `query`.
+    ///
+    /// A column that is missing from `row` never matches.
+    fn row_matches(row: &Row, query: &Row) -> bool {
+        query
+            .iter()
+            .all(|(column, wanted)| row.get(column) == Some(wanted))
+    }
+
+    // -------------------------------------------------------
+    // Public API: CRUD, lookups, sheet/column management
+    // -------------------------------------------------------
+
+    /// Return all rows that match EVERY key-value pair in `query`.
+    ///
+    /// If `query` is `None` or an empty map, every row matches, so all rows are returned
+    /// (prefer `select_all` to say so). Returns `None` if no rows match.
+    /// If one of the query columns is indexed (see `build_index`), only the rows the index
+    /// points to are checked.
+    pub fn select(&self, query: Option<&Row>) -> Option<Vec<Row>> {
+        let result: Vec<Row> = self.select_iter(query).cloned().collect();
+        if result.is_empty() {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    /// Lazily yield the rows matching `query` (all rows for `None`) by reference, in sheet
+    /// order, without cloning them. Uses a column index like `select` does.
+    pub fn select_iter<'a>(&'a self, query: Option<&'a Row>) -> impl Iterator<Item = &'a Row> + 'a {
+        let query = query.map(|q| self.normalize_query(q));
+        let candidates = query.as_deref().and_then(|q| self.indexed_candidates(q));
+        let (indexed, all) = match candidates {
+            Some(candidates) => (candidates.iter(), 0..0),
+            None => ([].iter(), 0..self.data.len()),
+        };
+        indexed
+            .copied()
+            .chain(all)
+            .map(move |idx| &self.data[idx])
+            .filter(move |row| query.as_deref().is_none_or(|q| Self::row_matches(row, q)))
+    }
+
+    /// Whether any row matches `query`, without cloning rows; stops at the first match.
+    pub fn any_match(&self, query: &Row) -> bool {
+        self.select_iter(Some(query)).next().is_some()
+    }
+
+    /// Same as `any_match`: whether any row matches `query`, without cloning rows.
+    pub fn exists(&self, query: &Row) -> bool {
+        self.any_match(query)
+    }
+
+    /// Number of rows matching `query` (all rows for `None`), without cloning them.
+    pub fn count(&self, query: Option<&Row>) -> usize {
+        self.select_iter(query).count()
+    }
+
+    /// A copy of every row, in sheet order (empty if the sheet has no data rows). Use
+    /// `to_rows` to borrow them instead.
+    pub fn select_all(&self) -> Vec<Row> {
+        self.to_rows().to_vec()
+    }
+
+    /// All rows, in sheet order, as a slice.
+    pub fn to_rows(&self) -> &[Row] {
+        &self.data
+    }
+
+    /// Index `column` so that `select` and `get_column_value` find rows by its value
+    /// without scanning every row. The index is kept up to date as the rows change; after
+    /// rows are edited through `filter_mut`, `iter_mut` or `get_row_mut`, lookups scan the
+    /// rows until the next save rebuilds it.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if the sheet has no column `column`.
+    pub fn build_index(&mut self, column: &str) -> Result<(), ExcelDbError> {
+        let column = &self.ensure_column(column)?;
+        let index = Self::index_column(&self.data, column);
+        self.indices.insert(column.to_string(), index);
+        if self.indices_stale {
+            self.rebuild_indices();
+        }
+        Ok(())
+    }
+
+    /// Remove the index on `column`, if any.
+    pub fn drop_index(&mut self, column: &str) {
+        self.indices.remove(column);
+    }
+
+    /// Whether `column` has an index built by `build_index`.
+    pub fn has_index(&self, column: &str) -> bool {
+        self.indices.contains_key(column)
+    }
+
+    /// Return page `page` (0-based) of the rows matching `query`, `page_size` rows per page,
+    /// along with the total number of matches. Pages past the end have no rows.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidPage` if `page_size` is zero.
+    pub fn select_page(
+        &self,
+        query: Option<&Row>,
+        page: usize,
+        page_size: usize,
+    ) -> Result<Page, ExcelDbError> {
+        if page_size == 0 {
+            return Err(ExcelDbError::InvalidPage(
+                "page_size must be at least 1".to_string(),
+            ));
+        }
+        let matching: Vec<&Row> = self.matching_rows(query);
+        let rows = matching
+            .iter()
+            .skip(page.saturating_mul(page_size))
+            .take(page_size)
+            .map(|row| (*row).clone())
+            .collect();
+        Ok(Page {
+            rows,
+            total_matching: matching.len(),
+            page,
+            page_size,
+            total_pages: matching.len().div_ceil(page_size),
+        })
+    }
+
+    /// Like `select`, but each returned row only holds the listed `columns` (in that
+    /// order), keyed by the header each one refers to (see `add_column_alias` and
+    /// `with_case_sensitivity`). Columns a row does not have are left out of it. The query
+    /// may use any column, projected or not.
+    pub fn select_columns(&self, query: Option<&Row>, columns: &[&str]) -> Option<Vec<Row>> {
+        let columns: Vec<&str> = columns
+            .iter()
+            .map(|&c| self.column_key(c).map_or(c, String::as_str))
+            .collect();
+        let result: Vec<Row> = self
+            .matching_rows(query)
+            .into_iter()
+            .map(|row| {
+                columns
+                    .iter()
+                    .filter_map(|&c| row.get(c).map(|v| (c.to_string(), v.clone())))
+                    .collect()
+            })
+            .collect();
+        if result.is_empty() {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    /// Return clones of all rows for which `predicate` returns `true`, for conditions an
+    /// exact-match `select` query cannot express, e.g.
+    /// `|row| age(row) > 30.0 && city(row) != "Seoul"`.
+    pub fn filter<F: Fn(&Row) -> bool>(&self, predicate: F) -> Vec<Row> {
+        self.data
+            .iter()
+            .filter(|row| predicate(row))
+            .cloned()
+            .collect()
+    }
+
+    /// Return mutable references to all rows for which `predicate` returns `true`, to edit
+    /// them in place. Changes made this way are not saved automatically; call `save`
+    /// afterwards. They are not checked by validators or recorded in the change log.
+    pub fn filter_mut<F: Fn(&Row) -> bool>(&mut self, predicate: F) -> Vec<&mut Row> {
+        self.rows_mut()
+            .iter_mut()
+            .filter(|row| predicate(row))
+            .collect()
+    }
+
+    /// Write the in-memory rows to the file, e.g. after editing them through `filter_mut`,
+    /// even with auto-save off.
+    ///
+    /// # Errors
+    ///
+    /// - `NoFilePath` for a database created with `from_rows`.
+    /// - Same as `save_data` otherwise.
+    pub fn save(&mut self) -> Result<(), ExcelDbError> {
+        self.save_now()
+    }
+
+    /// Copy the current file into `backup_dir` (created if missing) under a timestamped
+    /// name such as `data_2024-06-15T10-30-00.123.xlsx.bak`, then save. Returns the backup's
+    /// path; backups made within the same millisecond get a counter instead of overwriting
+    /// each other.
+    ///
+    /// # Errors
+    ///
+    /// - Propagates I/O errors from creating the directory or copying the file; nothing
+    ///   is saved in that case.
+    /// - Same as `save_data`.
+    pub fn save_with_backup<P: AsRef<Path>>(
+        &mut self,
+        backup_dir: P,
+    ) -> Result<PathBuf, ExcelDbError> {
+        self.ensure_writable()?;
+        let backup = self.backup_to(backup_dir.as_ref())?;
+        let auto_backup = self.auto_backup.take();
+        let saved = self.save_now();
+        self.auto_backup = auto_backup;
+        saved?;
+        Ok(backup)
+    }
+
+    /// Choose the format saves write. `Csv` and `Json` write the active sheet's rows to
+    /// the file path with its extension replaced by `.csv` / `.json`, leaving the workbook
+    /// untouched. A database opened from a `.csv` or `.json` file saves in that format.
+    pub fn set_save_format(&mut self, fmt: SaveFormat) {
+        self.save_format = fmt;
+    }
+
+    /// Back the file up into `dir` before every save (see `save_with_backup`), or stop
+    /// doing so with `None`.
+    pub fn set_auto_backup(&mut self, dir: Option<PathBuf>) {
+        self.auto_backup = dir;
+    }
+
+    /// Return all rows satisfying `op`, which can combine AND, OR and NOT conditions.
+    /// Returns `None` if no rows match.
+    pub fn query(&self, op: &QueryOp) -> Option<Vec<Row>> {
+        let result: Vec<Row> = self
+            .data
+            .iter()
+            .filter(|row| op.matches(row))
+            .cloned()
+            .collect();
+        if result.is_empty() {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    /// Like `select`, but deserializes each matching row into `T`.
+    ///
+    /// Column names map to field names. Numbers and booleans are passed through as-is (a
+    /// whole number also fills an integer field), dates as their text form, and blank cells
+    /// as `null` (so they fill `Option` fields).
+    /// Returns an empty `Vec` when nothing matches.
+    ///
+    /// # Errors
+    ///
+    /// `DeserializeError` if a matching row cannot be converted into `T`.
+    pub fn select_as<T: for<'de> Deserialize<'de>>(
+        &self,
+        query: Option<&Row>,
+    ) -> Result<Vec<T>, ExcelDbError> {
+        self.matching_rows(query)
+            .into_iter()
+            .map(|row| {
+                let object: serde_json::Map<String, serde_json::Value> =
+                    row.iter().map(|(k, v)| (k.clone(), v.to_json())).collect();
+                serde_json::from_value(serde_json::Value::Object(object))
+                    .map_err(|e| ExcelDbError::DeserializeError(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Return all rows whose `[start_col, end_col]` interval overlaps `[query_start, query_end]`,
+    /// i.e. `row.start <= query_end && row.end >= query_start`.
+    ///
+    /// Works for numeric text and for date-times (either `DateTime` values or date text).
+    /// Rows with a missing or incomparable bound are left out.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if no row has `start_col` or `end_col`.
+    pub fn select_overlapping_range(
+        &self,
+        start_col: &str,
+        end_col: &str,
+        query_start: CellValue,
+        query_end: CellValue,
+    ) -> Result<Vec<Row>, ExcelDbError> {
+        let start_col = &self.ensure_column(start_col)?;
+        let end_col = &self.ensure_column(end_col)?;
+        let overlaps = |row: &Row| -> Option<bool> {
+            let start = row.get(start_col)?;
+            let end = row.get(end_col)?;
+            let starts_before_end = Self::compare_values(start, &query_end)? != Ordering::Greater;
+            let ends_after_start = Self::compare_values(end, &query_start)? != Ordering::Less;
+            Some(starts_before_end && ends_after_start)
+        };
+        Ok(self
+            .data
+            .iter()
+            .filter(|row| overlaps(row).unwrap_or(false))
+            .cloned()
+            .collect())
+    }
+
+    /// Detect the type of every column from its non-empty values (see `InferredType`).
+    ///
+    /// A column gets the most specific type all of its values satisfy; integers and floats
+    /// together make `Float`, any other combination is `Mixed`. Columns with no values are
+    /// `Text`.
+    pub fn infer_column_types(&self) -> HashMap<String, InferredType> {
+        self.headers
+            .iter()
+            .map(|col| {
+                let mut types = self
+                    .data
+                    .iter()
+                    .filter_map(|row| row.get(col))
+                    .filter(|v| !v.is_empty())
+                    .map(CellValue::inferred_type);
+                let first = types.next().unwrap_or(InferredType::Text);
+                let inferred = types.fold(first, |acc, t| match (acc, t) {
+                    (a, b) if a == b => a,
+                    (InferredType::Integer, InferredType::Float)
+                    | (InferredType::Float, InferredType::Integer) => InferredType::Float,
+                    _ => InferredType::Mixed,
+                });
+                (col.clone(), inferred)
+            })
+            .collect()
+    }
+
+    /// Convert every non-empty value in `col` to the `CellValue` variant for `target`
+    /// (`Number`, `Bool`, `Date` / `DateTime` or `Text`), then save. Values that cannot be
+    /// converted are left unchanged. Returns the number of converted cells.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if the sheet has no column `col`.
+    /// - `InvalidParameter` if `target` is `Mixed`.
+    /// - Propagates any error from `save_data`.
+    pub fn cast_column(&mut self, col: &str, target: InferredType) -> Result<usize, ExcelDbError> {
+        self.ensure_writable()?;
+        let col = &self.ensure_column(col)?;
+        if target == InferredType::Mixed {
+            return Err(ExcelDbError::InvalidParameter(
+                "cannot cast a column to Mixed".to_string(),
+            ));
+        }
+        let mut converted = 0;
+        for idx in 0..self.data.len() {
+            let cast = self.data[idx]
+                .get(col)
+                .filter(|value| !value.is_empty())
+                .and_then(|value| value.cast(target));
+            if let Some(cast) = cast {
+                let mut row = self.data[idx].clone();
+                row.insert(col.clone(), cast);
+                self.replace_row(idx, row);
+                converted += 1;
+            }
+        }
+        self.save_data()?;
+        Ok(converted)
+    }
+
+    /// Return all rows whose `column` value is of the variant `type_filter`.
+    ///
+    /// Rows without the column are left out. Useful for spotting stray values in a column
+    /// that should hold a single type.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if no row has `column`.
+    pub fn select_by_value_type(
+        &self,
+        column: &str,
+        type_filter: CellValueType,
+    ) -> Result<Vec<Row>, ExcelDbError> {
+        let column = &self.ensure_column(column)?;
+        Ok(self
+            .data
+            .iter()
+            .filter(|row| {
+                row.get(column)
+                    .is_some_and(|v| v.value_type() == type_filter)
+            })
+            .cloned()
+            .collect())
+    }
+
+    /// Return all rows whose `column` value (as text) matches the regex `pattern`.
+    ///
+    /// The pattern is unanchored, so it matches anywhere in the cell. Returns `None` if no
+    /// rows match, like `select`.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if no row has `column`.
+    /// - `InvalidPattern` if `pattern` is not a valid regex.
+    pub fn select_matching(
+        &self,
+        column: &str,
+        pattern: &str,
+    ) -> Result<Option<Vec<Row>>, ExcelDbError> {
+        let re = Regex::new(pattern)?;
+        self.select_by_regex(column, &re)
+    }
+
+    /// Same as `select_matching`, but the pattern ignores case (`(?i)`).
+    ///
+    /// # Errors
+    ///
+    /// Same as `select_matching`.
+    pub fn select_matching_ci(
+        &self,
+        column: &str,
+        pattern: &str,
+    ) -> Result<Option<Vec<Row>>, ExcelDbError> {
+        let re = RegexBuilder::new(pattern).case_insensitive(true).build()?;
+        self.select_by_regex(column, &re)
+    }
+
+    /// Same as `select_matching_ci`, but the pattern and the cell text are first normalised
+    /// to Unicode NFC, so a precomposed `é` (U+00E9) and an `e` followed by a combining
+    /// acute accent (U+0301) match each other. Case is ignored with Unicode case folding
+    /// (`É` / `é`, `Σ` / `σ` / `ς`), and classes such as `\p{L}` match whole characters.
+    ///
+    /// # Errors
+    ///
+    /// Same as `select_matching`.
+    pub fn select_matching_unicode(
+        &self,
+        column: &str,
+        pattern: &str,
+    ) -> Result<Option<Vec<Row>>, ExcelDbError> {
+        let pattern: String = pattern.nfc().collect();
+        let re = RegexBuilder::new(&pattern).case_insensitive(true).build()?;
+        self.select_by_text(column, |text| re.is_match(&text.nfc().collect::<String>()))
+    }
+
+    /// Return all rows for which `predicate` returns `true`; the read counterpart of
+    /// `update_where` and `delete_where`. Returns an empty `Vec` if no rows match. The same
+    /// as `filter`.
+    pub fn select_where<F: Fn(&Row) -> bool>(&self, predicate: F) -> Vec<Row> {
+        self.filter(predicate)
+    }
+
+    /// Return all rows whose `col` value is a number between `min` and `max` (including
+    /// both ends if `inclusive`). Text is parsed as a number; rows whose value is not
+    /// numeric are left out. A wrapper over `filter`.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if the sheet has no column `col`.
+    /// - `InvalidParameter` if `min` is greater than `max` or either is NaN.
+    pub fn select_where_in_range(
+        &self,
+        col: &str,
+        min: f64,
+        max: f64,
+        inclusive: bool,
+    ) -> Result<Vec<Row>, ExcelDbError> {
+        let col = &self.ensure_column(col)?;
+        if min.is_nan() || max.is_nan() || min > max {
+            return Err(ExcelDbError::InvalidParameter(format!(
+                "invalid range {}..{}",
+                min, max
+            )));
+        }
+        Ok(self.filter(|row| {
+            row.get(col).and_then(CellValue::as_f64).is_some_and(|n| {
+                if inclusive {
+                    min <= n && n <= max
+                } else {
+                    min < n && n < max
+                }
+            })
+        }))
+    }
+
+    /// Return all rows whose `col` value (as text) contains `substring`, case-sensitively.
+    /// Returns no rows if the sheet has no column `col`. A wrapper over `filter`.
+    pub fn select_where_contains(&self, col: &str, substring: &str) -> Vec<Row> {
+        let col = self.column_key(col).map_or(col, String::as_str);
+        self.filter(|row| {
+            row.get(col)
+                .is_some_and(|v| v.to_string().contains(substring))
+        })
+    }
+
+    /// Find the first row where `search_column == search_value` and return that row's `target_column` value.
+    ///
+    /// Returns `Ok(None)` if no row matches. Uses the index on `search_column` if there is
+    /// one (see `build_index`).
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if the sheet has no `search_column` or no `target_column`.
+    pub fn get_column_value(
+        &self,
+        search_column: &str,
+        search_value: &CellValue,
+        target_column: &str,
+    ) -> Result<Option<CellValue>, ExcelDbError> {
+        let search_column = &self.ensure_column(search_column)?;
+        let target_column = &self.ensure_column(target_column)?;
+        let found = match self.column_index(search_column) {
+            Some(index) => index
+                .get(search_value)
+                .and_then(|rows| rows.first())
+                .map(|&idx| &self.data[idx]),
+            None => self
+                .data
+                .iter()
+                .find(|row| row.get(search_column) == Some(search_value)),
+        };
+        Ok(found.and_then(|row| row.get(target_column).cloned()))
+    }
+
+    /// Return the `target_column` value of EVERY row where `search_column == search_value`.
+    ///
+    /// Rows that match but lack `target_column` are skipped. Returns an empty vec if
+    /// nothing matches.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if the sheet has no `search_column` or no `target_column`.
+    pub fn get_column_values_all(
+        &self,
+        search_column: &str,
+        search_value: &CellValue,
+        target_column: &str,
+    ) -> Result<Vec<CellValue>, ExcelDbError> {
+        let search_column = &self.ensure_column(search_column)?;
+        let target_column = &self.ensure_column(target_column)?;
+        Ok(self
+            .data
+            .iter()
+            .filter(|row| row.get(search_column) == Some(search_value))
+            .filter_map(|row| row.get(target_column).cloned())
+            .collect())
+    }
+
+    /// Same as `get_column_values_all`, but returns `Ok(None)` instead of an empty vec,
+    /// mirroring `select`.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if the sheet has no `search_column` or no `target_column`.
+    pub fn get_column_values(
+        &self,
+        search_column: &str,
+        search_value: &CellValue,
+        target_column: &str,
+    ) -> Result<Option<Vec<CellValue>>, ExcelDbError> {
+        let values = self.get_column_values_all(search_column, search_value, target_column)?;
+        Ok(if values.is_empty() {
+            None
+        } else {
+            Some(values)
+        })
+    }
+
+    /// Insert a new row into the in-memory data and immediately save to the Excel file.
+    ///
+    /// If auto-timestamping is enabled, the current UTC date-time is stored in the
+    /// timestamp column unless `new_row` already provides a value for it. Likewise, an
+    /// auto-increment column receives the next ID unless `new_row` provides one.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error from `save_data`.
+    pub fn insert(&mut self, new_row: Row) -> Result<(), ExcelDbError> {
+        self.insert_many(vec![new_row])
+    }
+
+    /// Insert `partial` after adding the schema's default value (see `Schema::default_value`)
+    /// for every column it lacks. Without a schema this is the same as `insert`.
+    ///
+    /// # Errors
+    ///
+    /// - `ValidationError` if the completed row breaks the schema, e.g. a required column
+    ///   with no default was not supplied.
+    /// - Propagates any error from `save_data`.
+    pub fn insert_with_defaults(&mut self, mut partial: Row) -> Result<(), ExcelDbError> {
+        if let Some(schema) = &self.schema {
+            for (column, value) in schema.defaults.iter() {
+                if !partial.contains_key(column) {
+                    partial.insert(column.clone(), value.clone());
+                }
+            }
+        }
+        self.insert(partial)
+    }
+
+    /// Append several rows and write the file once, instead of once per row as calling
+    /// `insert` in a loop would.
+    ///
+    /// # Errors
+    ///
+    /// - `ReadOnly` if the database was opened with `open_read_only`.
+    /// - `ValidationError` if any row breaks the schema; no row is inserted.
+    /// - I/O or spreadsheet errors if saving fails.
+    pub fn insert_many(&mut self, rows: Vec<Row>) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        let rows = self.prepare_new_rows(rows)?;
+        self.push_rows(rows);
+        self.save_data()?;
+        Ok(())
+    }
+
+    /// Fill the generated columns of rows about to be inserted and check them against the
+    /// schema. On a violation the auto-increment counter is left as it was.
+    fn prepare_new_rows(&mut self, mut rows: Vec<Row>) -> Result<Vec<Row>, ExcelDbError> {
+        let counter = self.auto_increment.clone();
+        for row in rows.iter_mut() {
+            self.fill_generated_columns(row);
+        }
+        if let Err(e) = self.validate_rows(rows.iter().enumerate()) {
+            self.auto_increment = counter;
+            return Err(e);
+        }
+        Ok(rows)
+    }
+
+    /// Check `(index, row)` pairs against the schema, if one is set, and the column validators.
+    fn validate_rows<'a, I>(&self, rows: I) -> Result<(), ExcelDbError>
+    where
+        I: IntoIterator<Item = (usize, &'a Row)>,
+    {
+        if self.schema.is_none() && self.validators.is_empty() {
+            return Ok(());
+        }
+        let violations: Vec<String> = rows
+            .into_iter()
+            .flat_map(|(idx, row)| {
+                self.row_violations(row)
+                    .into_iter()
+                    .map(move |v| format!("row {}: {}", idx, v))
+            })
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ExcelDbError::ValidationError(violations))
+        }
+    }
+
+    /// Every schema rule and column validator `row` breaks, as human-readable messages, with
+    /// the validators checked in the order they were added. Validators only see the columns
+    /// present in `row`.
+    fn row_violations(&self, row: &Row) -> Vec<String> {
+        let mut violations = self
+            .schema
+            .as_ref()
+            .map(|s| s.violations(row))
+            .unwrap_or_default();
+        for (column, validator) in &self.validators {
+            if let Some(value) = row.get(column).filter(|v| !(validator.0)(v)) {
+                violations.push(format!("column \"{}\" rejected \"{}\"", column, value));
+            }
+        }
+        violations
+    }
+
+    /// Reject rows passed to `insert` / `update` (and the bulk, single-row and merging
+    /// variants such as `update_at`, `update_first_matching` and `merge_row_fields`) whose
+    /// value in `col` makes `validator` return `false`, replacing any earlier validator for
+    /// `col` (which keeps its place in the order violations are reported). Rows already
+    /// loaded are not checked; see `validate_column`.
+    pub fn add_column_validator<F>(&mut self, col: &str, validator: F)
+    where
+        F: Fn(&CellValue) -> bool + Send + Sync + 'static,
+    {
+        self.validators
+            .insert(col.to_string(), ColumnValidator(Arc::new(validator)));
+    }
+
+    /// Stop validating the values of `col`.
+    pub fn remove_column_validator(&mut self, col: &str) {
+        self.validators.shift_remove(col);
+    }
+
+    /// Number of loaded rows whose value in `col` fails its validator (0 if `col` has none).
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if `col` is not a column of the sheet.
+    pub fn validate_column(&self, col: &str) -> Result<usize, ExcelDbError> {
+        let col = &self.ensure_column(col)?;
+        let Some(validator) = self.validators.get(col) else {
+            return Ok(0);
+        };
+        Ok(self
+            .data
+            .iter()
+            .filter_map(|row| row.get(col))
+            .filter(|v| !(validator.0)(v))
+            .count())
+    }
+
+    /// Validate every row passed to `insert` / `update` (and the bulk variants) against
+    /// `schema` from now on. Rows already loaded are not checked; see `validate_all`.
+    pub fn set_schema(&mut self, schema: Schema) {
+        self.schema = Some(schema);
+    }
+
+    /// Stop validating rows against a schema.
+    pub fn clear_schema(&mut self) {
+        self.schema = None;
+    }
+
+    /// Check every loaded row against the schema and column validators. Succeeds if neither
+    /// is set.
+    ///
+    /// # Errors
+    ///
+    /// `ValidationError` listing each violation as `"row <index>: <problem>"` (0-based).
+    pub fn validate_all(&self) -> Result<(), ExcelDbError> {
+        self.validate_rows(self.data.iter().enumerate())
+    }
+
+    /// Serialize `value` into a row (one column per field, named like the field) and
+    /// `insert` it. The counterpart of `select_as`.
+    ///
+    /// # Errors
+    ///
+    /// `DeserializeError` if `value` does not serialize to a flat struct or map;
+    /// otherwise propagates any error from `insert`.
+    pub fn insert_typed<T: Serialize>(&mut self, value: &T) -> Result<(), ExcelDbError> {
+        let json = serde_json::to_value(value)
+            .map_err(|e| ExcelDbError::DeserializeError(e.to_string()))?;
+        let serde_json::Value::Object(object) = json else {
+            return Err(ExcelDbError::DeserializeError(
+                "value must serialize to a struct or map".to_string(),
+            ));
+        };
+        let row: Row = object
+            .iter()
+            .map(|(k, v)| (k.clone(), CellValue::from_json(v)))
+            .collect();
+        self.insert(row)
+    }
+
+    /// Same as `insert_typed`: serialize `value` into a row and `insert` it.
+    ///
+    /// # Errors
+    ///
+    /// Same as `insert_typed`.
+    pub fn insert_from<T: Serialize>(&mut self, value: &T) -> Result<(), ExcelDbError> {
+        self.insert_typed(value)
+    }
+
+    /// Append a row to the bottom of the sheet. Same as `insert`, but reads better
+    /// when the sheet is used as an append-only log.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error from `insert`.
+    pub fn log_row(&mut self, row: Row) -> Result<(), ExcelDbError> {
+        self.insert(row)
+    }
+
+    /// Stamp every subsequently inserted row with the current UTC date-time in `column_name`.
+    pub fn enable_auto_timestamp(&mut self, column_name: &str) {
+        self.auto_timestamp_column = Some(column_name.to_string());
+    }
+
+    /// Stop adding timestamps to inserted rows.
+    pub fn disable_auto_timestamp(&mut self) {
+        self.auto_timestamp_column = None;
+    }
+
+    /// Update all rows matching `query` by merging in `update_data`, then save.
+    ///
+    /// Returns the number of rows in which at least one value actually changed, so
+    /// matching rows that already hold `update_data` are not counted.
+    ///
+    /// # Errors
+    ///
+    /// - `ValidationError` if an updated row would break the schema; nothing is changed.
+    /// - Propagates any error from `save_data`.
+    pub fn update(&mut self, query: &Row, update_data: &Row) -> Result<usize, ExcelDbError> {
+        self.ensure_writable()?;
+        let (_, modified) = self.apply_update(query, update_data)?;
+        self.save_data()?;
+        Ok(modified)
+    }
+
+    /// Update all rows for which `predicate` returns `true` by merging in `update_data`,
+    /// then save. `predicate` sees each row as it was before the update.
+    ///
+    /// Returns the number of rows in which at least one value actually changed, as `update`
+    /// does.
+    ///
+    /// # Errors
+    ///
+    /// - `ValidationError` if an updated row would break the schema; nothing is changed.
+    /// - Propagates any error from `save_data`.
+    pub fn update_where<F: Fn(&Row) -> bool>(
+        &mut self,
+        predicate: F,
+        update_data: &Row,
+    ) -> Result<usize, ExcelDbError> {
+        self.ensure_writable()?;
+        let (_, modified) = self.apply_update_where(predicate, update_data)?;
+        self.save_data()?;
+        Ok(modified)
+    }
+
+    /// Whether two rows hold different values (column order is ignored).
+    fn rows_differ(a: &Row, b: &Row) -> bool {
+        a != b
+    }
+
+    /// Merge `update_data` into every row matching `query`, in memory only, after checking
+    /// the merged rows against the schema. Returns the number of rows matched and the
+    /// number of them whose values changed.
+    fn apply_update(
+        &mut self,
+        query: &Row,
+        update_data: &Row,
+    ) -> Result<(usize, usize), ExcelDbError> {
+        let query = &self.normalize_query(query);
+        self.apply_update_where(|row| Self::row_matches(row, query), update_data)
+    }
+
+    /// `apply_update` for the rows for which `predicate` returns `true`.
+    fn apply_update_where<F: Fn(&Row) -> bool>(
+        &mut self,
+        predicate: F,
+        update_data: &Row,
+    ) -> Result<(usize, usize), ExcelDbError> {
+        let update_data = &self.normalize_query(update_data);
+        if self.schema.is_some() || !self.validators.is_empty() {
+            let merged: Vec<(usize, Row)> = self
+                .data
+                .iter()
+                .enumerate()
+                .filter(|(_, row)| predicate(row))
+                .map(|(idx, row)| {
+                    let mut row = row.clone();
+                    row.extend(update_data.iter().map(|(k, v)| (k.clone(), v.clone())));
+                    (idx, row)
+                })
+                .collect();
+            self.validate_rows(merged.iter().map(|(idx, row)| (*idx, row)))?;
+        }
+        let mut matched = 0;
+        let mut modified = 0;
+        for idx in 0..self.data.len() {
+            if !predicate(&self.data[idx]) {
+                continue;
+            }
+            let mut row = self.data[idx].clone();
+            for (u_key, u_val) in update_data.iter() {
+                row.insert(u_key.clone(), u_val.clone());
+            }
+            matched += 1;
+            if self.replace_row(idx, row) {
+                modified += 1;
+            }
+        }
+        Ok((matched, modified))
+    }
+
+    /// Replace every cell equal to `find` with `replace`, in all columns, then save once.
+    /// Returns the number of cells replaced.
+    ///
+    /// # Errors
+    ///
+    /// - `ValidationError` if a replaced cell breaks the schema; nothing is changed.
+    /// - Propagates any error from `save_data`.
+    pub fn find_replace(
+        &mut self,
+        find: &CellValue,
+        replace: CellValue,
+    ) -> Result<usize, ExcelDbError> {
+        self.ensure_writable()?;
+        self.replace_cells(None, find, replace)
+    }
+
+    /// Replace every cell of `col` equal to `find` with `replace`, then save once.
+    /// Returns the number of cells replaced.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if the sheet has no column `col`.
+    /// - `ValidationError` if a replaced cell breaks the schema; nothing is changed.
+    /// - Propagates any error from `save_data`.
+    pub fn find_replace_in_column(
+        &mut self,
+        col: &str,
+        find: &CellValue,
+        replace: CellValue,
+    ) -> Result<usize, ExcelDbError> {
+        self.ensure_writable()?;
+        let col = &self.ensure_column(col)?;
+        self.replace_cells(Some(col), find, replace)
+    }
+
+    /// Replace the cells equal to `find` (only in `column`, if given) and save once.
+    fn replace_cells(
+        &mut self,
+        column: Option<&str>,
+        find: &CellValue,
+        replace: CellValue,
+    ) -> Result<usize, ExcelDbError> {
+        let in_scope = |key: &String| column.is_none_or(|c| c == key);
+        let mut replaced = 0;
+        let changed: Vec<(usize, Row)> = self
+            .data
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| row.iter().any(|(k, v)| in_scope(k) && v == find))
+            .map(|(idx, row)| {
+                let mut row = row.clone();
+                for (k, v) in row.iter_mut() {
+                    if in_scope(k) && v == find {
+                        *v = replace.clone();
+                        replaced += 1;
+                    }
+                }
+                (idx, row)
+            })
+            .collect();
+        if changed.is_empty() {
+            return Ok(0);
+        }
+        self.validate_rows(changed.iter().map(|(idx, row)| (*idx, row)))?;
+        for (idx, row) in changed {
+            self.replace_row(idx, row);
+        }
+        self.save_data()?;
+        Ok(replaced)
+    }
+
+    /// Update the rows whose `key_columns` equal those of `row`, or insert `row` if
+    /// there are none, then save once.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if `key_columns` is empty or `row` lacks one of them.
+    /// - `ValidationError` if the inserted or updated rows would break the schema.
+    /// - Propagates any error from `save_data`.
+    pub fn upsert(&mut self, key_columns: &[&str], row: Row) -> Result<UpsertResult, ExcelDbError> {
+        self.ensure_writable()?;
+        if key_columns.is_empty() {
+            return Err(ExcelDbError::InvalidParameter(
+                "at least one key column is required".to_string(),
+            ));
+        }
+        let mut query = Row::new();
+        for &key in key_columns {
+            let value = row.get(key).ok_or_else(|| {
+                ExcelDbError::InvalidParameter(format!(
+                    "row has no value for key column \"{}\"",
+                    key
+                ))
+            })?;
+            query.insert(key.to_string(), value.clone());
+        }
+
+        let result = match self.apply_update(&query, &row)?.0 {
+            0 => {
+                let rows = self.prepare_new_rows(vec![row])?;
+                self.push_rows(rows);
+                UpsertResult::Inserted
+            }
+            updated => UpsertResult::Updated(updated),
+        };
+        self.save_data()?;
+        Ok(result)
+    }
+
+    /// Delete all rows that match `query`, then save back to the file.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error from `save_data`.
+    pub fn delete(&mut self, query: &Row) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        let query = &self.normalize_query(query);
+        self.remove_where(|row| Self::row_matches(row, query));
+        self.save_data()?;
+        Ok(())
+    }
+
+    /// Delete all rows for which `predicate` returns `true`, then save. Returns the number
+    /// of rows deleted.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error from `save_data`.
+    pub fn delete_where<F: Fn(&Row) -> bool>(
+        &mut self,
+        predicate: F,
+    ) -> Result<usize, ExcelDbError> {
+        self.ensure_writable()?;
+        let deleted = self.remove_where(predicate);
+        self.save_data()?;
+        Ok(deleted)
+    }
+
+    /// Remove the rows for which `predicate` returns `true` from memory and return how many
+    /// were removed.
+    fn remove_where<F: Fn(&Row) -> bool>(&mut self, predicate: F) -> usize {
+        self.remove_rows(|_, row| predicate(row)).len()
+    }
+
+    /// Append `rows` in memory, logging each as a `Change::Insert` if change tracking is on.
+    ///
+    /// This, `replace_row` and `remove_rows` are the only ways methods change rows, so the
+    /// change log sees every change.
+    fn push_rows(&mut self, rows: Vec<Row>) {
+        if let Some(log) = &mut self.change_log {
+            log.extend(rows.iter().cloned().map(Change::Insert));
+        }
+        self.data.extend(rows);
+    }
+
+    /// Replace the row at `index` with `row` in memory. Returns whether the row changed, in
+    /// which case a `Change::Update` is logged if change tracking is on.
+    fn replace_row(&mut self, index: usize, row: Row) -> bool {
+        let before = std::mem::replace(&mut self.data[index], row);
+        let changed = Self::rows_differ(&before, &self.data[index]);
+        if let (true, Some(log)) = (changed, &mut self.change_log) {
+            log.push(Change::Update {
+                before,
+                after: self.data[index].clone(),
+            });
+        }
+        changed
+    }
+
+    /// Remove the rows for which `remove(index, row)` returns `true` from memory, logging
+    /// each as a `Change::Delete` if change tracking is on. Returns the removed rows in
+    /// their original order.
+    fn remove_rows<F: FnMut(usize, &Row) -> bool>(&mut self, mut remove: F) -> Vec<Row> {
+        let mut removed = Vec::new();
+        let mut kept = Vec::with_capacity(self.data.len());
+        for (idx, row) in std::mem::take(&mut self.data).into_iter().enumerate() {
+            if remove(idx, &row) {
+                removed.push(row);
+            } else {
+                kept.push(row);
+            }
+        }
+        self.data = kept;
+        if let Some(log) = &mut self.change_log {
+            log.extend(removed.iter().cloned().map(Change::Delete));
+        }
+        removed
+    }
+
+    /// Remove and return the row at `index` (which must exist), logging it like
+    /// `remove_rows`.
+    fn take_row(&mut self, index: usize) -> Row {
+        self.remove_rows(|idx, _| idx == index)
+            .pop()
+            .expect("index is a row")
+    }
+
+    /// Delete the rows at the given zero-based `indices` with a single save, returning the
+    /// removed rows in ascending index order.
+    ///
+    /// Rows are removed from the highest index down, so earlier removals never shift the
+    /// positions of later ones. Duplicate indices are ignored.
+    ///
+    /// # Errors
+    ///
+    /// - `IndexOutOfBounds` if any index is `>=` the number of rows; nothing is removed.
+    /// - Propagates any error from `save_data`.
+    pub fn delete_at_indices(&mut self, indices: Vec<usize>) -> Result<Vec<Row>, ExcelDbError> {
+        self.ensure_writable()?;
+        if let Some(&bad) = indices.iter().find(|&&idx| idx >= self.data.len()) {
+            return Err(ExcelDbError::IndexOutOfBounds(bad));
+        }
+        let indices: HashSet<usize> = indices.into_iter().collect();
+        let removed = self.remove_rows(|idx, _| indices.contains(&idx));
+        self.save_data()?;
+        Ok(removed)
+    }
+
+    /// Delete the rows at zero-based indices `start..end` (half-open) with a single save,
+    /// returning the number of rows removed.
+    ///
+    /// # Errors
+    ///
+    /// - `IndexOutOfBounds` if `start` is not a row index, `end` is past the last row, or
+    ///   `start > end`; nothing is removed.
+    /// - Propagates any error from `save_data`.
+    pub fn delete_range(&mut self, start: usize, end: usize) -> Result<usize, ExcelDbError> {
+        self.ensure_writable()?;
+        if start >= self.data.len() || start > end {
+            return Err(ExcelDbError::IndexOutOfBounds(start));
+        }
+        if end > self.data.len() {
+            return Err(ExcelDbError::IndexOutOfBounds(end));
+        }
+        self.remove_rows(|idx, _| (start..end).contains(&idx));
+        self.save_data()?;
+        Ok(end - start)
+    }
+
+    /// Delete up to `n` rows from the start of the sheet, returning how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error from `save_data`.
+    pub fn delete_first_n(&mut self, n: usize) -> Result<usize, ExcelDbError> {
+        let n = n.min(self.data.len());
+        if n == 0 {
+            self.ensure_writable()?;
+            return Ok(0);
+        }
+        self.delete_range(0, n)
+    }
+
+    /// Delete up to `n` rows from the end of the sheet, returning how many were removed.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error from `save_data`.
+    pub fn delete_last_n(&mut self, n: usize) -> Result<usize, ExcelDbError> {
+        let n = n.min(self.data.len());
+        if n == 0 {
+            self.ensure_writable()?;
+            return Ok(0);
+        }
+        self.delete_range(self.data.len() - n, self.data.len())
+    }
+
+    /// Delete every data row and save. The header row (and so `get_column_names`) is kept.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error from `save_data`.
+    pub fn clear(&mut self) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        self.remove_rows(|_, _| true);
+        self.save_data()?;
+        Ok(())
+    }
+
+    /// Iterate over the loaded rows without cloning them.
+    pub fn iter(&self) -> impl Iterator<Item = &Row> + '_ {
+        self.data.iter()
+    }
+
+    /// Iterate mutably over the loaded rows. Changes are not saved automatically; call
+    /// `save` afterwards. They are not checked by validators or recorded in the change log.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Row> + '_ {
+        self.rows_mut().iter_mut()
+    }
+
+    /// The row at zero-based `index`, if there is one.
+    pub fn get_row(&self, index: usize) -> Option<&Row> {
+        self.data.get(index)
+    }
+
+    /// Mutable access to the row at zero-based `index`. Changes are not saved
+    /// automatically; call `save` afterwards. They are not checked by validators or
+    /// recorded in the change log.
+    pub fn get_row_mut(&mut self, index: usize) -> Option<&mut Row> {
+        self.rows_mut().get_mut(index)
+    }
+
+    /// The cell at column `col_letter` (e.g. `"B"` or `"AA"`) and 1-based Excel row
+    /// `excel_row`, as it would be addressed in Excel. Returns `None` for header rows and
+    /// for cells outside the data.
+    pub fn get_cell_by_coordinate(&self, col_letter: &str, excel_row: u32) -> Option<&CellValue> {
+        let column = self.headers.get(Self::col_letter_to_index(col_letter)?)?;
+        self.data
+            .get(self.excel_row_to_index(excel_row)?)?
+            .get(column)
+    }
+
+    /// Set the cell at column `col_letter` and 1-based Excel row `excel_row`, then save.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if `col_letter` is not a column letter.
+    /// - `ColumnNotFound(col_letter)` if the sheet has no column at that letter.
+    /// - `IndexOutOfBounds(excel_row)` if `excel_row` is a header row or past the last data row.
+    /// - `ValidationError` if the new value breaks the schema.
+    /// - Propagates any error from `save_data`.
+    pub fn set_cell_by_coordinate(
+        &mut self,
+        col_letter: &str,
+        excel_row: u32,
+        value: CellValue,
+    ) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        let col = Self::col_letter_to_index(col_letter).ok_or_else(|| {
+            ExcelDbError::InvalidParameter(format!("'{}' is not a column letter", col_letter))
+        })?;
+        let column = self
+            .headers
+            .get(col)
+            .cloned()
+            .ok_or_else(|| ExcelDbError::ColumnNotFound(col_letter.to_string()))?;
+        let index = self
+            .excel_row_to_index(excel_row)
+            .filter(|&i| i < self.data.len())
+            .ok_or(ExcelDbError::IndexOutOfBounds(excel_row as usize))?;
+        let mut row = self.data[index].clone();
+        row.insert(column, value);
+        self.validate_rows([(index, &row)])?;
+        self.replace_row(index, row);
+        self.save_data()?;
+        Ok(())
+    }
+
+    /// Replace the row at zero-based `index` with `data`, then save.
+    ///
+    /// # Errors
+    ///
+    /// - `IndexOutOfBounds` if there is no row at `index`.
+    /// - `ValidationError` if `data` breaks the schema.
+    /// - Propagates any error from `save_data`.
+    pub fn update_at(&mut self, index: usize, data: Row) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        if index >= self.data.len() {
+            return Err(ExcelDbError::IndexOutOfBounds(index));
+        }
+        self.validate_rows([(index, &data)])?;
+        self.replace_row(index, data);
+        self.save_data()?;
+        Ok(())
+    }
+
+    /// Remove the row at zero-based `index`, save, and return the removed row.
+    ///
+    /// # Errors
+    ///
+    /// - `IndexOutOfBounds` if there is no row at `index`.
+    /// - Propagates any error from `save_data`.
+    pub fn delete_at(&mut self, index: usize) -> Result<Row, ExcelDbError> {
+        self.ensure_writable()?;
+        if index >= self.data.len() {
+            return Err(ExcelDbError::IndexOutOfBounds(index));
+        }
+        let removed = self.take_row(index);
+        self.save_data()?;
+        Ok(removed)
+    }
+
+    /// Group rows that share the same values in `key_columns` (every column if empty) and
+    /// return the groups with more than one row, in order of first appearance. A missing
+    /// cell counts as a value of its own.
+    pub fn find_duplicates(&self, key_columns: &[&str]) -> Vec<Vec<Row>> {
+        self.duplicate_groups(key_columns)
+            .into_iter()
+            .map(|group| {
+                group
+                    .into_iter()
+                    .map(|idx| self.data[idx].clone())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Remove duplicate rows (as found by `find_duplicates`), keeping one row per group
+    /// according to `keep`, then save. Returns the number of rows removed; if there were
+    /// none the file is not rewritten.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error from `save_data`.
+    pub fn deduplicate(
+        &mut self,
+        key_columns: &[&str],
+        keep: KeepStrategy,
+    ) -> Result<usize, ExcelDbError> {
+        self.ensure_writable()?;
+        let mut drop: HashSet<usize> = HashSet::new();
+        for group in self.duplicate_groups(key_columns) {
+            let kept = match keep {
+                KeepStrategy::First => group[0],
+                KeepStrategy::Last => group[group.len() - 1],
+            };
+            drop.extend(group.into_iter().filter(|&idx| idx != kept));
+        }
+        if drop.is_empty() {
+            return Ok(0);
+        }
+        self.remove_rows(|idx, _| drop.contains(&idx));
+        self.save_data()?;
+        Ok(drop.len())
+    }
+
+    /// Positions of rows sharing the same `key_columns` values (every column if empty),
+    /// grouped in order of first appearance; only groups of two or more are returned.
+    fn duplicate_groups(&self, key_columns: &[&str]) -> Vec<Vec<usize>> {
+        let columns: Vec<&str> = if key_columns.is_empty() {
+            self.headers.iter().map(String::as_str).collect()
+        } else {
+            key_columns.to_vec()
+        };
+        let mut groups: IndexMap<Vec<Option<&CellValue>>, Vec<usize>> = IndexMap::new();
+        for (idx, row) in self.data.iter().enumerate() {
+            let key = columns.iter().map(|&c| row.get(c)).collect();
+            groups.entry(key).or_default().push(idx);
+        }
+        groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect()
+    }
+
+    /// Update only the first row matching `query` by merging in `update_data`, then save.
+    ///
+    /// Returns `true` if a row was updated, `false` if nothing matched (in which case
+    /// the file is not rewritten).
+    ///
+    /// # Errors
+    ///
+    /// - `ValidationError` if the updated row would break the schema or a column validator;
+    ///   nothing is changed.
+    /// - Propagates any error from `save_data`.
+    pub fn update_first_matching(
+        &mut self,
+        query: &Row,
+        update_data: &Row,
+    ) -> Result<bool, ExcelDbError> {
+        self.ensure_writable()?;
+        let query = &self.normalize_query(query);
+        let update_data = &self.normalize_query(update_data);
+        let Some(index) = self
+            .data
+            .iter()
+            .position(|row| Self::row_matches(row, query))
+        else {
+            return Ok(false);
+        };
+        let mut row = self.data[index].clone();
+        for (u_key, u_val) in update_data.iter() {
+            row.insert(u_key.clone(), u_val.clone());
+        }
+        self.validate_rows([(index, &row)])?;
+        self.replace_row(index, row);
+        self.save_data()?;
+        Ok(true)
+    }
+
+    /// Merge `extra_fields` into the first row where `key_column == key_value`, then save.
+    ///
+    /// With `overwrite` set, every field in `extra_fields` replaces the row's value; otherwise
+    /// only columns the row does not have yet are added. Returns `false` (without saving)
+    /// if no row matched.
+    ///
+    /// # Errors
+    ///
+    /// - `ValidationError` if the merged row would break the schema or a column validator;
+    ///   nothing is changed.
+    /// - Propagates any error from `save_data`.
+    pub fn merge_row_fields(
+        &mut self,
+        key_column: &str,
+        key_value: &CellValue,
+        extra_fields: Row,
+        overwrite: bool,
+    ) -> Result<bool, ExcelDbError> {
+        self.ensure_writable()?;
+        let key_column = self
+            .column_key(key_column)
+            .map_or(key_column, String::as_str);
+        let Some(index) = self
+            .data
+            .iter()
+            .position(|row| row.get(key_column) == Some(key_value))
+        else {
+            return Ok(false);
+        };
+        let extra_fields = self.normalize_query(&extra_fields).into_owned();
+        let mut row = self.data[index].clone();
+        for (key, value) in extra_fields {
+            if overwrite {
+                row.insert(key, value);
+            } else {
+                row.entry(key).or_insert(value);
+            }
+        }
+        self.validate_rows([(index, &row)])?;
+        self.replace_row(index, row);
+        self.save_data()?;
+        Ok(true)
+    }
+
+    /// Delete only the first row matching `query`, save, and return the removed row.
+    ///
+    /// Returns `None` if nothing matched (in which case the file is not rewritten).
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error from `save_data`.
+    pub fn delete_first_matching(&mut self, query: &Row) -> Result<Option<Row>, ExcelDbError> {
+        self.ensure_writable()?;
+        let query = &self.normalize_query(query);
+        let Some(index) = self
+            .data
+            .iter()
+            .position(|row| Self::row_matches(row, query))
+        else {
+            return Ok(None);
+        };
+        let removed = self.take_row(index);
+        self.save_data()?;
+        Ok(Some(removed))
+    }
+
+    /// Write the current rows as RFC 4180 CSV, starting with a header row.
+    ///
+    /// # Errors
+    ///
+    /// `CsvError` if writing to `writer` fails.
+    pub fn to_csv<W: Write>(&self, writer: W) -> Result<(), ExcelDbError> {
+        Self::write_csv(writer, &self.headers, &self.data)
+    }
+
+    /// Serialize the current rows to a JSON array with one object per row. Each cell keeps
+    /// its variant (e.g. `{"age": {"Number": 30.0}}`), so `import_json` restores it exactly.
+    ///
+    /// # Errors
+    ///
+    /// `JsonError` if serialization fails.
+    pub fn to_json_string(&self) -> Result<String, ExcelDbError> {
+        serde_json::to_string(&self.data).map_err(|e| ExcelDbError::JsonError(e.to_string()))
+    }
+
+    /// Parse a JSON array of row objects (as written by `to_json_string`), append them with
+    /// `insert_many`, and return how many rows were inserted.
+    ///
+    /// # Errors
+    ///
+    /// - `JsonError` if `json` is not an array of row objects.
+    /// - Propagates any error from `insert_many`.
+    pub fn import_json(&mut self, json: &str) -> Result<usize, ExcelDbError> {
+        let rows: Vec<Row> =
+            serde_json::from_str(json).map_err(|e| ExcelDbError::JsonError(e.to_string()))?;
+        let count = rows.len();
+        self.insert_many(rows)?;
+        Ok(count)
+    }
+
+    /// Write the current rows as tab-separated values: a header row, then one line per row.
+    ///
+    /// Tabs, line breaks and backslashes inside values are escaped as `\t`, `\n`, `\r`
+    /// and `\\`, so every record stays on one line.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any I/O error from writing the file.
+    pub fn export_tsv<P: AsRef<Path>>(&self, path: P) -> Result<(), ExcelDbError> {
+        let headers = &self.headers;
+        let mut out = String::new();
+        let header_line: Vec<String> = headers.iter().map(|h| Self::escape_tsv(h)).collect();
+        out.push_str(&header_line.join("\t"));
+        out.push('\n');
+        for row in self.data.iter() {
+            let fields: Vec<String> = headers
+                .iter()
+                .map(|h| {
+                    row.get(h)
+                        .map(|v| Self::escape_tsv(&v.to_string()))
+                        .unwrap_or_default()
+                })
+                .collect();
+            out.push_str(&fields.join("\t"));
+            out.push('\n');
+        }
+        fs::write(path, out)?;
+        Ok(())
+    }
+
+    /// Replace the sheet's rows with the contents of a TSV file written by `export_tsv`,
+    /// then save. Returns the number of rows imported.
+    ///
+    /// The first line is the header row; an empty one (as exported for a sheet without
+    /// columns) means there are no columns. Every later line is a row, even an empty one,
+    /// except for the newline ending the file. All values are loaded as `CellValue::Text`;
+    /// missing trailing fields become `CellValue::Empty`.
+    ///
+    /// TSV keeps no cell types, so a round trip through `export_tsv` is lossy in the same way
+    /// as loading a `.csv` file: `Number(3.0)` comes back as `Text("3")`, and booleans and
+    /// dates as their text form. Use `to_json_string` / `import_json` to keep them.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if the file is empty, so it has no header line.
+    /// - Propagates I/O errors and any error from `save_data`.
+    pub fn import_tsv<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, ExcelDbError> {
+        self.ensure_writable()?;
+        let content = fs::read_to_string(path)?;
+        if content.is_empty() {
+            return Err(ExcelDbError::InvalidParameter(
+                "TSV file has no header line".to_string(),
+            ));
+        }
+        let content = content.strip_suffix('\n').unwrap_or(&content);
+        let mut lines = content
+            .split('\n')
+            .map(|l| l.strip_suffix('\r').unwrap_or(l));
+        let headers: Vec<String> = match lines.next() {
+            Some(line) if !line.is_empty() => line.split('\t').map(Self::unescape_tsv).collect(),
+            _ => Vec::new(),
+        };
+        let mut rows: Vec<Row> = Vec::new();
+        for line in lines {
+            let fields: Vec<String> = line.split('\t').map(Self::unescape_tsv).collect();
+            let row: Row = headers
+                .iter()
+                .enumerate()
+                .map(|(idx, h)| {
+                    let value = fields
+                        .get(idx)
+                        .cloned()
+                        .map_or(CellValue::Empty, CellValue::Text);
+                    (h.clone(), value)
+                })
+                .collect();
+            rows.push(row);
+        }
+        let count = rows.len();
+        self.data = rows;
+        self.headers = headers;
+        self.save_data()?;
+        Ok(count)
+    }
+
+    /// Format the header row with `style` (a `HeaderStyle` or a `CellStyle`) on this and
+    /// every later save.
+    ///
+    /// The style is only stored here; it is written out by the next operation that saves.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidParameter` if `font_size` is not a positive number.
+    pub fn set_header_style<S: Into<HeaderStyle>>(&mut self, style: S) -> Result<(), ExcelDbError> {
+        let style = style.into();
+        if let Some(size) = style.font_size {
+            if !size.is_finite() || size <= 0.0 {
+                return Err(ExcelDbError::InvalidParameter(format!(
+                    "font_size must be positive, got {}",
+                    size
+                )));
+            }
+        }
+        self.header_style = Some(style);
+        Ok(())
+    }
+
+    /// Format the cell in column `col` of the data row at `row_index` with `style` on this
+    /// and every later save, replacing any earlier style for that cell.
+    ///
+    /// The style belongs to the position, not the row: it does not follow a row that moves
+    /// because rows before it were deleted or reordered. Styles for positions with no cell
+    /// at save time are skipped.
+    pub fn set_cell_style(&mut self, col: &str, row_index: usize, style: CellStyle) {
+        let col = self.column_key(col).map_or(col, String::as_str).to_string();
+        self.cell_styles.insert((row_index, col), style);
+    }
+
+    /// Set the width of `col`, in characters, on this and every later save.
+    ///
+    /// The width is only stored here; it is written out by the next operation that saves.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if the sheet has no column `col`.
+    /// - `InvalidParameter` if `width` is not a positive number.
+    pub fn set_column_width(&mut self, col: &str, width: f64) -> Result<(), ExcelDbError> {
+        let col = self.ensure_column(col)?;
+        if !width.is_finite() || width <= 0.0 {
+            return Err(ExcelDbError::InvalidParameter(format!(
+                "column width must be positive, got {}",
+                width
+            )));
+        }
+        self.column_widths.insert(col, width);
+        Ok(())
+    }
+
+    /// Size every column to its longest value (header included), in characters, plus a
+    /// little padding, replacing any width set with `set_column_width`. Like
+    /// `set_column_width`, the widths are written on the next save.
+    pub fn auto_fit_columns(&mut self) {
+        for header in &self.headers {
+            let longest = self
+                .data
+                .iter()
+                .filter_map(|row| row.get(header))
+                .map(|v| v.to_string().chars().count())
+                .chain([header.chars().count()])
+                .max()
+                .unwrap_or(0);
+            self.column_widths
+                .insert(header.clone(), longest as f64 + AUTO_FIT_PADDING);
+        }
+    }
+
+    /// Write a formatted report of the current rows to a new workbook at `output_path`.
+    ///
+    /// The report has a merged title row, an optional merged subtitle row, the data table
+    /// (header styled with `config.header_style`, data rows striped with
+    /// `config.stripe_color`) and an optional merged footer row; with a single column these
+    /// rows are plain cells, as a one-cell merge would corrupt the file. The sheet keeps this
+    /// database's sheet name. `self.file_path` is not touched, so this also works on a
+    /// read-only instance.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any I/O or spreadsheet errors from writing the file.
+    pub fn generate_report<P: AsRef<Path>>(
+        &self,
+        output_path: P,
+        config: ReportConfig,
+    ) -> Result<(), ExcelDbError> {
+        let headers = &self.headers;
+        let last_col = Self::column_letter(headers.len().max(1));
+        let mut ws = Worksheet::new();
+        let mut excel_row: u32 = 1;
+
+        // Title block: merged across the table width (a single column needs no merge)
+        let merged_line = |ws: &mut Worksheet, row: u32, text: &str, style: &HeaderStyle| {
+            let mut cell = Cell::new(1, row, UCellValue::from(text.to_string()));
+            style.apply_to(cell.get_style_mut());
+            ws.add_cell(cell);
+            if headers.len() > 1 {
+                ws.add_merge_cells(format!("A{}:{}{}", row, last_col, row));
+            }
+        };
+        let title_style = HeaderStyle {
+            bold: true,
+            font_size: Some(16.0),
+            ..HeaderStyle::default()
+        };
+        merged_line(&mut ws, excel_row, &config.title, &title_style);
+        excel_row += 1;
+        if let Some(subtitle) = &config.subtitle {
+            merged_line(&mut ws, excel_row, subtitle, &HeaderStyle::default());
+            excel_row += 1;
+        }
+        excel_row += 1; // blank spacer row
+
+        // Table header
+        for (col_idx, header) in headers.iter().enumerate() {
+            let mut cell = Cell::new(
+                (col_idx + 1) as u32,
+                excel_row,
+                UCellValue::from(header.clone()),
+            );
+            config.header_style.apply_to(cell.get_style_mut());
+            ws.add_cell(cell);
+        }
+        excel_row += 1;
+
+        // Data rows, striping every other one
+        for (row_idx, row_map) in self.data.iter().enumerate() {
+            let striped = config.stripe_color.filter(|_| row_idx % 2 == 1);
+            for (col_idx, header) in headers.iter().enumerate() {
+                let value = row_map.get(header).cloned().unwrap_or(CellValue::Empty);
+                let mut cell = value.into_cell((col_idx + 1) as u32, excel_row);
+                if let Some(color) = striped {
+                    cell.get_style_mut().set_background_color(color.to_argb());
+                }
+                ws.add_cell(cell);
+            }
+            excel_row += 1;
+        }
+
+        if let Some(footer) = &config.footer {
+            excel_row += 1; // blank spacer row
+            merged_line(&mut ws, excel_row, footer, &HeaderStyle::default());
+        }
+
+        let mut book = umya_spreadsheet::new_file_empty_worksheet();
+        book.add_worksheet(&self.sheet_name, ws);
+        write_xlsx(&book, output_path.as_ref(), None)?;
+        Ok(())
+    }
+
+    /// Add a new sheet with the given name. If `initial_data` is provided and non-empty,
+    /// the column names of its Rows form the header row, and each Row fills a data row below it.
+    ///
+    /// # Errors
+    ///
+    /// - `SheetAlreadyExists` if a sheet with that name already exists (to avoid
+    ///   overwriting).
+    /// - Propagates I/O or spreadsheet errors if writing fails.
+    pub fn add_sheet(
+        &self,
+        new_sheet_name: &str,
+        initial_data: Option<Vec<Row>>,
+    ) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        let mut book = self.workbook()?;
+        if book.has_sheet(new_sheet_name) {
+            return Err(ExcelDbError::SheetAlreadyExists(new_sheet_name.to_string()));
+        }
+        let rows = initial_data.unwrap_or_default();
+        let headers = Self::collect_headers(&rows);
+        let ws = Self::build_worksheet(&headers, &rows, None, 1);
+
+        book.add_worksheet(new_sheet_name, ws);
+        self.write_workbook(&book)?;
+        Ok(())
+    }
+
+    /// Remove the sheet `sheet_name` from the workbook and save.
+    ///
+    /// # Errors
+    ///
+    /// - `CannotRemoveActiveSheet` if `sheet_name` is the sheet this instance is using;
+    ///   see `remove_sheet_unchecked`.
+    /// - `SheetNotFound` if there is no such sheet.
+    /// - Propagates I/O or spreadsheet errors from writing the workbook.
+    pub fn remove_sheet(&self, sheet_name: &str) -> Result<(), ExcelDbError> {
+        if sheet_name == self.sheet_name {
+            return Err(ExcelDbError::CannotRemoveActiveSheet(
+                sheet_name.to_string(),
+            ));
+        }
+        self.remove_sheet_from_file(sheet_name)
+    }
+
+    /// Like `remove_sheet`, but also allows removing the active sheet, in which case this
+    /// instance switches to the first remaining sheet (as `switch_sheet` would, but without
+    /// saving the removed sheet's rows).
+    ///
+    /// # Errors
+    ///
+    /// - `SheetNotFound` if there is no such sheet.
+    /// - `CannotRemoveActiveSheet` if `sheet_name` is the active sheet and the workbook has
+    ///   no other sheet.
+    /// - `InvalidParameter` if `sheet_name` is the active sheet and a transaction is open.
+    /// - `NoHeaders` if the sheet to switch to is empty; nothing is removed.
+    /// - Propagates I/O or spreadsheet errors from writing the workbook.
+    pub fn remove_sheet_unchecked(&mut self, sheet_name: &str) -> Result<(), ExcelDbError> {
+        if sheet_name != self.sheet_name {
+            return self.remove_sheet_from_file(sheet_name);
+        }
+        if self.in_transaction() {
+            return Err(ExcelDbError::InvalidParameter(
+                "cannot remove the active sheet during a transaction".to_string(),
+            ));
+        }
+        let next = self
+            .workbook()?
+            .get_sheet_names()
+            .iter()
+            .find(|name| *name != sheet_name)
+            .cloned()
+            .ok_or_else(|| ExcelDbError::CannotRemoveActiveSheet(sheet_name.to_string()))?;
+        let (headers, data) = self.load_sheet(&next)?;
+        self.remove_sheet_from_file(sheet_name)?;
+        self.set_active_sheet(&next, headers, data);
+        Ok(())
+    }
+
+    /// Rename the sheet `old_name` to `new_name` in place, keeping its data and formatting,
+    /// and save. If `old_name` is the active sheet this instance follows the rename.
+    ///
+    /// # Errors
+    ///
+    /// - `SheetNotFound` if there is no sheet called `old_name`.
+    /// - `SheetAlreadyExists` if a sheet called `new_name` already exists.
+    /// - Propagates I/O or spreadsheet errors from writing the workbook.
+    pub fn rename_sheet(&mut self, old_name: &str, new_name: &str) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        let mut book = self.workbook()?;
+        if book.has_sheet(new_name) {
+            return Err(ExcelDbError::SheetAlreadyExists(new_name.to_string()));
+        }
+        let ws = book
+            .get_sheet_by_name_mut(old_name)
+            .ok_or_else(|| ExcelDbError::SheetNotFound(old_name.to_string()))?;
+        ws.set_name(new_name);
+        self.write_workbook(&book)?;
+        drop(book);
+        if old_name == self.sheet_name {
+            self.sheet_name = new_name.to_string();
+        }
+        Ok(())
+    }
+
+    /// Turn on Excel's AutoFilter for every column of the active sheet, covering the header
+    /// row and all data rows, and save. Later saves resize the filter to the rows saved.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if the sheet has no columns.
+    /// - Same as `set_auto_filter_range` otherwise.
+    pub fn set_auto_filter(&self) -> Result<(), ExcelDbError> {
+        if self.headers.is_empty() {
+            return Err(ExcelDbError::InvalidParameter(
+                "cannot filter a sheet without columns".to_string(),
+            ));
+        }
+        self.set_auto_filter_range(1, self.headers.len() as u32)
+    }
+
+    /// Turn on Excel's AutoFilter for the 1-based columns `start_col..=end_col` of the active
+    /// sheet, covering the header row and all data rows, and save.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if `start_col` is zero or greater than `end_col`.
+    /// - `SheetNotFound` if the active sheet is missing from the file.
+    /// - Propagates I/O or spreadsheet errors from writing the workbook.
+    pub fn set_auto_filter_range(&self, start_col: u32, end_col: u32) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        if start_col == 0 || start_col > end_col {
+            return Err(ExcelDbError::InvalidParameter(format!(
+                "invalid auto-filter columns {}..={}",
+                start_col, end_col
+            )));
+        }
+        let range = self.auto_filter_range(start_col as usize, end_col as usize);
+        self.edit_active_sheet(|ws| {
+            ws.set_auto_filter(range);
+        })
+    }
+
+    /// The AutoFilter range over the 1-based columns `start_col..=end_col`, from the header
+    /// row down to the last data row, e.g. `"A1:C11"` for ten rows.
+    fn auto_filter_range(&self, start_col: usize, end_col: usize) -> String {
+        format!(
+            "{}{}:{}{}",
+            Self::column_letter(start_col),
+            self.header_row,
+            Self::column_letter(end_col),
+            self.header_row as usize + self.data.len()
+        )
+    }
+
+    /// Remove Excel's AutoFilter from the active sheet, if it has one, and save.
+    ///
+    /// # Errors
+    ///
+    /// - `SheetNotFound` if the active sheet is missing from the file.
+    /// - Propagates I/O or spreadsheet errors from writing the workbook.
+    pub fn clear_auto_filter(&self) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        self.edit_active_sheet(|ws| {
+            ws.remove_auto_filter();
+        })
+    }
+
+    /// Freeze the top `count` rows of the active sheet (e.g. `1` for a header in row 1) so
+    /// they stay visible while scrolling, and save. Replaces any earlier freeze.
+    ///
+    /// # Errors
+    ///
+    /// Same as `freeze_pane`.
+    pub fn freeze_rows(&self, count: u32) -> Result<(), ExcelDbError> {
+        self.freeze_pane(count, 0)
+    }
+
+    /// Freeze the leftmost `count` columns of the active sheet, and save. Replaces any
+    /// earlier freeze.
+    ///
+    /// # Errors
+    ///
+    /// Same as `freeze_pane`.
+    pub fn freeze_columns(&self, count: u32) -> Result<(), ExcelDbError> {
+        self.freeze_pane(0, count)
+    }
+
+    /// Freeze the top `row` rows and the leftmost `col` columns of the active sheet at once,
+    /// and save. Replaces any earlier freeze; `freeze_pane(0, 0)` removes it. The panes are
+    /// kept by later saves.
+    ///
+    /// # Errors
+    ///
+    /// - `ReadOnly` if the database was opened with `open_read_only`.
+    /// - `SheetNotFound` if the active sheet is missing from the file.
+    /// - Propagates I/O or spreadsheet errors from writing the workbook.
+    pub fn freeze_pane(&self, row: u32, col: u32) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        if row == 0 && col == 0 {
+            return self.clear_freeze();
+        }
+        let top_left = format!("{}{}", Self::column_letter(col as usize + 1), row + 1);
+        self.edit_active_sheet(|ws| {
+            let mut pane = Pane::default();
+            pane.set_horizontal_split(col as f64)
+                .set_vertical_split(row as f64)
+                .set_top_left_cell(top_left)
+                .set_state(PaneStateValues::Frozen);
+            ws.get_sheet_view_mut().set_pane(pane);
+        })
+    }
+
+    /// Unfreeze the rows and columns of the active sheet, if any are frozen, and save.
+    ///
+    /// # Errors
+    ///
+    /// - `ReadOnly` if the database was opened with `open_read_only`.
+    /// - `SheetNotFound` if the active sheet is missing from the file.
+    /// - Propagates I/O or spreadsheet errors from writing the workbook.
+    pub fn clear_freeze(&self) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        self.edit_active_sheet(|ws| {
+            ws.get_sheet_view_mut().remove_pane();
+        })
+    }
+
+    /// Apply `edit` to the active sheet of the in-memory workbook and write the workbook.
+    fn edit_active_sheet<F: FnOnce(&mut Worksheet)>(&self, edit: F) -> Result<(), ExcelDbError> {
+        let mut book = self.workbook()?;
+        let ws = book
+            .get_sheet_by_name_mut(&self.sheet_name)
+            .ok_or_else(|| ExcelDbError::SheetNotFound(self.sheet_name.clone()))?;
+        edit(ws);
+        self.write_workbook(&book)
+    }
+
+    /// Copy the sheet `source_sheet` to a new sheet `dest_sheet` in the same workbook, with
+    /// its formatting, and save. The active sheet is copied from the in-memory rows.
+    ///
+    /// # Errors
+    ///
+    /// - `SheetNotFound` if there is no sheet called `source_sheet`.
+    /// - `SheetAlreadyExists` if a sheet called `dest_sheet` already exists.
+    /// - Propagates I/O or spreadsheet errors from writing the workbook.
+    pub fn duplicate_sheet(
+        &self,
+        source_sheet: &str,
+        dest_sheet: &str,
+    ) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        let mut book = self.workbook()?;
+        if book.has_sheet(dest_sheet) {
+            return Err(ExcelDbError::SheetAlreadyExists(dest_sheet.to_string()));
+        }
+        let mut ws = if source_sheet == self.sheet_name {
+            self.rebuild_worksheet(&book)
+        } else {
+            book.get_sheet_by_name(source_sheet)
+                .ok_or_else(|| ExcelDbError::SheetNotFound(source_sheet.to_string()))?
+                .clone()
+        };
+        ws.set_name(dest_sheet);
+        book.add_worksheet(dest_sheet, ws);
+        self.write_workbook(&book)?;
+        Ok(())
+    }
+
+    /// Delete `sheet_name` from the workbook and write it.
+    fn remove_sheet_from_file(&self, sheet_name: &str) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        let mut book = self.workbook()?;
+        if !book.has_sheet(sheet_name) {
+            return Err(ExcelDbError::SheetNotFound(sheet_name.to_string()));
+        }
+        book.remove_sheet_by_name(sheet_name);
+        self.write_workbook(&book)?;
+        Ok(())
+    }
+
+    /// Partition the current rows by the sheet name `sheet_selector` returns for each one,
+    /// and write every group to that sheet in a single workbook save.
+    ///
+    /// Missing sheets are created; existing ones are overwritten with the group. Every
+    /// target sheet gets the same header row as the current sheet. Returns the number of
+    /// rows written per sheet.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if the selector names the currently loaded sheet, which would
+    ///   overwrite it behind the in-memory data's back.
+    /// - Propagates I/O or spreadsheet errors from writing the workbook.
+    pub fn write_rows_to_matching_sheets<F>(
+        &self,
+        sheet_selector: F,
+    ) -> Result<HashMap<String, usize>, ExcelDbError>
+    where
+        F: Fn(&Row) -> String,
+    {
+        self.ensure_writable()?;
+        let mut order: Vec<String> = Vec::new();
+        let mut groups: HashMap<String, Vec<Row>> = HashMap::new();
+        for row in self.data.iter() {
+            let target = sheet_selector(row);
+            if target == self.sheet_name {
+                return Err(ExcelDbError::InvalidParameter(format!(
+                    "cannot redirect rows into the active sheet \"{}\"",
+                    target
+                )));
+            }
+            if !groups.contains_key(&target) {
+                order.push(target.clone());
+            }
+            groups.entry(target).or_default().push(row.clone());
+        }
+
+        let headers = &self.headers;
+        let mut book = self.workbook()?;
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for name in order {
+            let rows = &groups[&name];
+            if book.has_sheet(&name) {
+                book.remove_sheet_by_name(&name);
+            }
+            book.add_worksheet(&name, Self::build_worksheet(headers, rows, None, 1));
+            counts.insert(name, rows.len());
+        }
+        self.write_workbook(&book)?;
+        Ok(counts)
+    }
+
+    /// Copy the rows matching `query` (all rows if `None`) into the existing sheet
+    /// `dest_sheet` of the same workbook, with a single read and write of the file.
+    ///
+    /// The rows are written into the destination's worksheet in place, so its header row,
+    /// column widths, cell styles and position in the workbook are kept. Values go under the
+    /// destination's columns by name; columns only the current sheet has are added after the
+    /// destination's last column. With `append`, the rows go after the destination's last
+    /// row; otherwise they overwrite its rows from the top and any rows left below are
+    /// cleared. Returns the number of rows copied.
+    ///
+    /// # Errors
+    ///
+    /// - `SheetNotFound(dest_sheet)` if the workbook has no sheet `dest_sheet`.
+    /// - `InvalidParameter` if `dest_sheet` is the currently loaded sheet.
+    /// - Propagates I/O or spreadsheet errors from writing the workbook.
+    pub fn copy_rows_to_sheet(
+        &self,
+        query: Option<&Row>,
+        dest_sheet: &str,
+        append: bool,
+    ) -> Result<usize, ExcelDbError> {
+        self.ensure_writable()?;
+        if dest_sheet == self.sheet_name {
+            return Err(ExcelDbError::InvalidParameter(format!(
+                "cannot copy rows into the active sheet \"{}\"",
+                dest_sheet
+            )));
+        }
+        let mut book = self.workbook()?;
+        let worksheet = book
+            .get_sheet_by_name_mut(dest_sheet)
+            .ok_or_else(|| ExcelDbError::SheetNotFound(dest_sheet.to_string()))?;
+
+        let loaded = Self::read_worksheet(
+            worksheet,
+            dest_sheet,
+            1,
+            1,
+            self.merged_cells,
+            self.duplicate_headers,
+        );
+        let mut headers = match loaded {
+            Ok((dest_headers, _)) => dest_headers,
+            Err(ExcelDbError::NoHeaders(_)) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        for header in &self.headers {
+            if !headers.contains(header) {
+                headers.push(header.clone());
+                let col = headers.len() as u32;
+                Self::write_cell(worksheet, col, 1, CellValue::Text(header.clone()));
+            }
+        }
+
+        let last_row = worksheet.get_highest_row();
+        let first_row = if append { last_row.max(1) + 1 } else { 2 };
+        let copied = self.matching_rows(query);
+        for (offset, row) in copied.iter().enumerate() {
+            let excel_row = first_row + offset as u32;
+            for (col_idx, header) in headers.iter().enumerate() {
+                let value = row.get(header).cloned().unwrap_or(CellValue::Empty);
+                Self::write_cell(worksheet, col_idx as u32 + 1, excel_row, value);
+            }
+        }
+        if !append {
+            for excel_row in first_row + copied.len() as u32..=last_row {
+                for col in 1..=headers.len() as u32 {
+                    Self::write_cell(worksheet, col, excel_row, CellValue::Empty);
+                }
+            }
+        }
+        let count = copied.len();
+        self.write_workbook(&book)?;
+        Ok(count)
+    }
+
+    /// Write `value` into the cell at the 1-based `col` / `row` of `worksheet`, keeping the
+    /// style of the cell it replaces.
+    fn write_cell(worksheet: &mut Worksheet, col: u32, row: u32, value: CellValue) {
+        let style = worksheet
+            .get_cell(col, row)
+            .map(|cell| cell.get_style().clone());
+        worksheet.add_cell(value.into_styled_cell(col, row, style));
+    }
+
+    /// Export every sheet of the workbook as `<sheet_name>.csv` inside a zip archive.
+    ///
+    /// The active sheet is exported from the in-memory rows (including unsaved changes);
+    /// the other sheets come from the in-memory workbook. Sheets without a header row become
+    /// empty CSVs.
+    ///
+    /// # Errors
+    ///
+    /// Propagates I/O, spreadsheet, CSV or zip errors.
+    pub fn export_sheets_to_zip<P: AsRef<Path>>(&self, output_zip: P) -> Result<(), ExcelDbError> {
+        let book = self.workbook()?;
+        let mut zip = zip::ZipWriter::new(fs::File::create(output_zip)?);
+        let options = zip::write::SimpleFileOptions::default();
+        for name in book.get_sheet_names() {
+            let (headers, rows) = if *name == self.sheet_name {
+                (self.headers.clone(), self.data.clone())
+            } else {
+                let worksheet = book.get_sheet_by_name(name).unwrap();
+                let loaded = Self::read_worksheet(
+                    worksheet,
+                    name,
+                    1,
+                    1,
+                    self.merged_cells,
+                    self.duplicate_headers,
+                );
+                match loaded {
+                    Ok(table) => table,
+                    Err(ExcelDbError::NoHeaders(_)) => (Vec::new(), Vec::new()),
+                    Err(e) => return Err(e),
+                }
+            };
+            zip.start_file(format!("{}.csv", name), options)?;
+            Self::write_csv(&mut zip, &headers, &rows)?;
+        }
+        zip.finish()?;
+        Ok(())
+    }
+
+    /// Check if a sheet with `sheet_name` exists in the workbook.
+    ///
+    /// # Errors
+    ///
+    /// `NoFilePath` for a database created with `from_rows`, `UnsupportedFormat` for a
+    /// `.csv` / `.json` file.
+    pub fn is_sheet_exists(&self, sheet_name: &str) -> Result<bool, ExcelDbError> {
+        let book = self.workbook()?;
+        Ok(book.has_sheet(sheet_name))
+    }
+
+    /// Get a list of all sheet names in the Excel file.
+    ///
+    /// # Errors
+    ///
+    /// `NoFilePath` for a database created with `from_rows`, `UnsupportedFormat` for a
+    /// `.csv` / `.json` file.
+    pub fn get_all_sheet_names(&self) -> Result<Vec<String>, ExcelDbError> {
+        let book = self.workbook()?;
+        Ok(book.get_sheet_names().to_vec())
+    }
+
+    /// Names of all workbook-level defined names (named ranges) in the Excel file.
+    ///
+    /// # Errors
+    ///
+    /// `NoFilePath` for a database created with `from_rows`, `UnsupportedFormat` for a
+    /// `.csv` / `.json` file.
+    pub fn list_named_ranges(&self) -> Result<Vec<String>, ExcelDbError> {
+        let book = self.workbook()?;
+        Ok(book
+            .get_defined_names()
+            .iter()
+            .map(|defined| defined.get_name().to_string())
+            .collect())
+    }
+
+    /// Read the cells of the named range `name` (matched case-insensitively, as Excel does)
+    /// from the workbook as last loaded or saved, one `Row` per sheet row of the range.
+    ///
+    /// Cells are keyed by the current sheet's column names, by position: the range's first
+    /// column in column `C` is read as the third header. Columns past the last header are
+    /// keyed by their letter. On the active sheet, header rows inside the range are skipped.
+    ///
+    /// # Errors
+    ///
+    /// - `NamedRangeNotFound(name)` if the workbook defines no such name.
+    /// - `InvalidParameter` if the name refers to something other than one cell range.
+    /// - `SheetNotFound` if the range's sheet is missing.
+    /// - `NoFilePath` / `UnsupportedFormat` as for `list_named_ranges`.
+    pub fn get_named_range(&self, name: &str) -> Result<Vec<Row>, ExcelDbError> {
+        let book = self.workbook()?;
+        let address = book
+            .get_defined_names()
+            .iter()
+            .find(|defined| defined.get_name().eq_ignore_ascii_case(name))
+            .map(|defined| defined.get_address())
+            .ok_or_else(|| ExcelDbError::NamedRangeNotFound(name.to_string()))?;
+        let (sheet, range) = match address.rsplit_once('!') {
+            Some((sheet, range)) => {
+                let sheet = match sheet.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+                    Some(quoted) => quoted.replace("''", "'"),
+                    None => sheet.to_string(),
+                };
+                (sheet, range)
+            }
+            None => (self.sheet_name.clone(), address.as_str()),
+        };
+        let ((first_col, first_row), (last_col, last_row)) =
+            Self::parse_range(range).ok_or_else(|| {
+                ExcelDbError::InvalidParameter(format!(
+                    "named range \"{}\" is not a cell range: {}",
+                    name, address
+                ))
+            })?;
+        let worksheet = book
+            .get_sheet_by_name(&sheet)
+            .ok_or_else(|| ExcelDbError::SheetNotFound(sheet.clone()))?;
+
+        // Zero-based index of the last header row, skipped on the active sheet
+        let last_header = (self.header_row + self.header_rows) as usize - 2;
+        let first_row = if sheet == self.sheet_name {
+            first_row.max(last_header + 1)
+        } else {
+            first_row
+        };
+        let mut rows = Vec::new();
+        for row_idx in first_row..=last_row {
+            let row: Row = (first_col..=last_col)
+                .map(|col_idx| {
+                    let key = self
+                        .headers
+                        .get(col_idx)
+                        .cloned()
+                        .unwrap_or_else(|| Self::column_letter(col_idx + 1));
+                    let value = worksheet
+                        .get_cell(col_idx as u32 + 1, row_idx as u32 + 1)
+                        .map_or(CellValue::Empty, CellValue::from_cell);
+                    (key, value)
+                })
+                .collect();
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
+    /// Return the row holding the largest value in `column` (see `CellValue::compare_loose`).
+    ///
+    /// Ties resolve to the first row encountered. Returns `None` if the column has no
+    /// non-empty values.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if no row has `column`.
+    pub fn find_max_row(&self, column: &str) -> Result<Option<Row>, ExcelDbError> {
+        self.find_extreme_row(column, Ordering::Greater)
+    }
+
+    /// Sort the in-memory rows by one or more columns, e.g. by date and then by name.
+    ///
+    /// Numeric columns sort numerically (so `"10"` comes after `"9"`), others by text.
+    /// The sort is stable: rows equal on every key keep their relative order. Columns
+    /// that do not exist are ignored. The file is rewritten with the new order on the
+    /// next write operation.
+    pub fn sort_by(&mut self, columns: &[(&str, SortOrder)]) {
+        Self::sort_rows(&mut self.data, columns);
+        self.rebuild_indices();
+        self.unsaved = true;
+    }
+
+    /// Return a sorted copy of the rows, leaving this instance untouched (see `sort_by`).
+    pub fn sorted(&self, columns: &[(&str, SortOrder)]) -> Vec<Row> {
+        let mut rows = self.data.clone();
+        Self::sort_rows(&mut rows, columns);
+        rows
+    }
+
+    /// Return the row holding the smallest value in `column` (see `CellValue::compare_loose`).
+    ///
+    /// Ties resolve to the first row encountered. Returns `None` if the column has no
+    /// non-empty values.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if no row has `column`.
+    pub fn find_min_row(&self, column: &str) -> Result<Option<Row>, ExcelDbError> {
+        self.find_extreme_row(column, Ordering::Less)
+    }
+
+    /// Sliding-window maximum of the numeric values in `column`.
+    ///
+    /// Position `i` holds the largest value among rows `i + 1 - window ..= i`, or `None`
+    /// if fewer than `window` rows precede it or the window has no numeric cell.
+    /// Non-numeric cells are skipped.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if no row has `column`.
+    /// - `InvalidParameter` if `window` is zero.
+    pub fn rolling_max(
+        &self,
+        column: &str,
+        window: usize,
+    ) -> Result<Vec<Option<CellValue>>, ExcelDbError> {
+        self.rolling_extreme(column, window, Ordering::Greater)
+    }
+
+    /// Sliding-window minimum of the numeric values in `column`.
+    ///
+    /// Same windowing rules as `rolling_max`.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if no row has `column`.
+    /// - `InvalidParameter` if `window` is zero.
+    pub fn rolling_min(
+        &self,
+        column: &str,
+        window: usize,
+    ) -> Result<Vec<Option<CellValue>>, ExcelDbError> {
+        self.rolling_extreme(column, window, Ordering::Less)
+    }
+
+    /// Combine each row with every row of `other_sheet` (in the same workbook) that has the
+    /// same non-empty `on` value, dropping rows without a match. Nothing is saved.
+    ///
+    /// Columns of `other_sheet` are added to the row; a name this sheet already uses is
+    /// prefixed with `<other_sheet>_`.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if either sheet has no column `on`.
+    /// - `SheetNotFound` / `NoHeaders` if `other_sheet` cannot be loaded.
+    pub fn inner_join(&self, other_sheet: &str, on: &str) -> Result<Vec<Row>, ExcelDbError> {
+        self.join(other_sheet, on, false)
+    }
+
+    /// Like `inner_join`, but rows without a match in `other_sheet` are kept, with its
+    /// columns empty.
+    ///
+    /// # Errors
+    ///
+    /// Same as `inner_join`.
+    pub fn left_join(&self, other_sheet: &str, on: &str) -> Result<Vec<Row>, ExcelDbError> {
+        self.join(other_sheet, on, true)
+    }
+
+    /// Shared implementation of `inner_join` and `left_join`.
+    fn join(
+        &self,
+        other_sheet: &str,
+        on: &str,
+        keep_unmatched: bool,
+    ) -> Result<Vec<Row>, ExcelDbError> {
+        let on = &self.ensure_column(on)?;
+        let (other_headers, other_rows) = if other_sheet == self.sheet_name {
+            (self.headers.clone(), self.data.clone())
+        } else {
+            let book = self.workbook()?;
+            Self::load_data(
+                &self.file_path,
+                Some(&book),
+                other_sheet,
+                1,
+                1,
+                self.merged_cells,
+                self.duplicate_headers,
+            )?
+        };
+        if !other_headers.contains(on) {
+            return Err(ExcelDbError::ColumnNotFound(on.clone()));
+        }
+
+        let mut by_key: HashMap<&CellValue, Vec<&Row>> = HashMap::new();
+        for row in other_rows.iter() {
+            if let Some(key) = row.get(on).filter(|v| !v.is_empty()) {
+                by_key.entry(key).or_default().push(row);
+            }
+        }
+        let joined_name = |column: &String| {
+            if self.headers.contains(column) {
+                format!("{}_{}", other_sheet, column)
+            } else {
+                column.clone()
+            }
+        };
+        let empty = CellValue::Empty;
+
+        let mut result = Vec::new();
+        for row in self.data.iter() {
+            let matches = row
+                .get(on)
+                .filter(|v| !v.is_empty())
+                .and_then(|key| by_key.get(key));
+            match matches {
+                Some(matches) => {
+                    for other in matches {
+                        let mut joined = row.clone();
+                        for column in other_headers.iter().filter(|c| *c != on) {
+                            let value = other.get(column).unwrap_or(&empty).clone();
+                            joined.insert(joined_name(column), value);
+                        }
+                        result.push(joined);
+                    }
+                }
+                None if keep_unmatched => {
+                    let mut joined = row.clone();
+                    for column in other_headers.iter().filter(|c| *c != on) {
+                        joined.insert(joined_name(column), empty.clone());
+                    }
+                    result.push(joined);
+                }
+                None => {}
+            }
+        }
+        Ok(result)
+    }
+
+    /// Partition the rows by the text of their `col` value, with groups in order of first
+    /// occurrence. Rows with an empty or missing value are grouped under `""`.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if the sheet has no column `col`.
+    pub fn group_by(&self, col: &str) -> Result<IndexMap<String, Vec<Row>>, ExcelDbError> {
+        let col = &self.ensure_column(col)?;
+        let mut groups: IndexMap<String, Vec<Row>> = IndexMap::new();
+        for row in self.data.iter() {
+            let key = match row.get(col) {
+                Some(value) if !value.is_empty() => value.to_string(),
+                _ => String::new(),
+            };
+            groups.entry(key).or_default().push(row.clone());
+        }
+        Ok(groups)
+    }
+
+    /// Group the rows as `group_by` does and reduce each group to one number with
+    /// `aggregator`. See `try_group_by_aggregate` for an aggregator that can fail.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if the sheet has no column `col`.
+    pub fn group_by_aggregate<F>(
+        &self,
+        col: &str,
+        aggregator: F,
+    ) -> Result<IndexMap<String, f64>, ExcelDbError>
+    where
+        F: Fn(&Vec<Row>) -> f64,
+    {
+        Ok(self
+            .group_by(col)?
+            .into_iter()
+            .map(|(key, rows)| {
+                let value = aggregator(&rows);
+                (key, value)
+            })
+            .collect())
+    }
+
+    /// Same as `group_by_aggregate`, but `aggregator` may fail, e.g. when it parses numeric
+    /// text with `?` (a `ParseFloatError` converts into `ExcelDbError::ParseFloatError`).
+    /// The first error is returned.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if the sheet has no column `col`.
+    /// - The first error returned by `aggregator`.
+    pub fn try_group_by_aggregate<F>(
+        &self,
+        col: &str,
+        aggregator: F,
+    ) -> Result<IndexMap<String, f64>, ExcelDbError>
+    where
+        F: Fn(&Vec<Row>) -> Result<f64, ExcelDbError>,
+    {
+        self.group_by(col)?
+            .into_iter()
+            .map(|(key, rows)| Ok((key, aggregator(&rows)?)))
+            .collect()
+    }
+
+    /// The distinct non-empty values of `col`, in order of first occurrence.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if the sheet has no column `col`.
+    pub fn get_unique_values(&self, col: &str) -> Result<Vec<CellValue>, ExcelDbError> {
+        let col = &self.ensure_column(col)?;
+        let mut seen: HashSet<&CellValue> = HashSet::new();
+        Ok(self
+            .data
+            .iter()
+            .filter_map(|row| row.get(col))
+            .filter(|value| !value.is_empty() && seen.insert(value))
+            .cloned()
+            .collect())
+    }
+
+    /// Number of distinct non-empty values of `col`, without collecting them.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if the sheet has no column `col`.
+    pub fn get_unique_count(&self, col: &str) -> Result<usize, ExcelDbError> {
+        let col = &self.ensure_column(col)?;
+        let unique: HashSet<&CellValue> = self
+            .data
+            .iter()
+            .filter_map(|row| row.get(col))
+            .filter(|value| !value.is_empty())
+            .collect();
+        Ok(unique.len())
+    }
+
+    /// Count, sum, mean, standard deviation, range and distinct values of `col` in a
+    /// single pass. Non-numeric cells count towards `non_empty` and `unique_count` only.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if the sheet has no column `col`.
+    pub fn column_stats(&self, col: &str) -> Result<ColumnStats, ExcelDbError> {
+        let col = &self.ensure_column(col)?;
+        let mut stats = ColumnStats {
+            total_rows: self.data.len(),
+            non_empty: 0,
+            numeric_count: 0,
+            sum: 0.0,
+            mean: 0.0,
+            std_dev: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            unique_count: 0,
+        };
+        let mut unique: HashSet<&CellValue> = HashSet::new();
+        // Welford's running sum of squared deviations from the mean
+        let mut m2 = 0.0;
+        for value in self.data.iter().filter_map(|row| row.get(col)) {
+            if value.is_empty() {
+                continue;
+            }
+            stats.non_empty += 1;
+            unique.insert(value);
+            let Some(x) = value.as_f64() else {
+                continue;
+            };
+            stats.numeric_count += 1;
+            stats.sum += x;
+            stats.min = stats.min.min(x);
+            stats.max = stats.max.max(x);
+            let delta = x - stats.mean;
+            stats.mean += delta / stats.numeric_count as f64;
+            m2 += delta * (x - stats.mean);
+        }
+        stats.unique_count = unique.len();
+        if stats.numeric_count == 0 {
+            stats.min = 0.0;
+            stats.max = 0.0;
+        } else if stats.numeric_count > 1 {
+            stats.std_dev = (m2 / (stats.numeric_count - 1) as f64).sqrt();
+        }
+        Ok(stats)
+    }
+
+    /// Sum of the numeric values in `col`. Empty cells are ignored; other non-numeric
+    /// cells are ignored and counted in `skipped`.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if no row has `col`.
+    /// - `NotANumericColumn` if `col` has no numeric values.
+    pub fn sum_column(&self, col: &str) -> Result<AggResult, ExcelDbError> {
+        let (values, skipped) = self.numeric_values(col)?;
+        Ok(AggResult {
+            value: values.iter().sum(),
+            skipped,
+        })
+    }
+
+    /// Mean of the numeric values in `col` (same skipping rules and errors as `sum_column`).
+    pub fn avg_column(&self, col: &str) -> Result<AggResult, ExcelDbError> {
+        let (values, skipped) = self.numeric_values(col)?;
+        let value = values.iter().sum::<f64>() / values.len() as f64;
+        Ok(AggResult { value, skipped })
+    }
+
+    /// Smallest numeric value in `col` (same skipping rules and errors as `sum_column`).
+    pub fn min_column(&self, col: &str) -> Result<AggResult, ExcelDbError> {
+        let (values, skipped) = self.numeric_values(col)?;
+        let value = values.into_iter().fold(f64::INFINITY, f64::min);
+        Ok(AggResult { value, skipped })
+    }
+
+    /// Largest numeric value in `col` (same skipping rules and errors as `sum_column`).
+    pub fn max_column(&self, col: &str) -> Result<AggResult, ExcelDbError> {
+        let (values, skipped) = self.numeric_values(col)?;
+        let value = values.into_iter().fold(f64::NEG_INFINITY, f64::max);
+        Ok(AggResult { value, skipped })
+    }
+
+    /// Check that the dates in `column` satisfy `check`, returning every violation as
+    /// `(row_index, value)`.
+    ///
+    /// Empty cells are ignored; each remaining date is compared with the previous non-empty
+    /// one (or, for `NoDuplicates`, with all earlier ones).
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if no row has `column`.
+    /// - `NotADateColumn` if a non-empty cell cannot be read as a date.
+    pub fn validate_date_sequence(
+        &self,
+        column: &str,
+        check: DateSequenceCheck,
+    ) -> Result<Vec<(usize, CellValue)>, ExcelDbError> {
+        let column = &self.ensure_column(column)?;
+        let mut dates: Vec<(usize, &CellValue, NaiveDateTime)> = Vec::new();
+        for (idx, row) in self.data.iter().enumerate() {
+            let Some(value) = row.get(column).filter(|v| !v.is_empty()) else {
+                continue;
+            };
+            let date = value
+                .as_datetime()
+                .ok_or_else(|| ExcelDbError::NotADateColumn(column.to_string()))?;
+            dates.push((idx, value, date));
+        }
+
+        let mut violations = Vec::new();
+        let mut seen: HashSet<NaiveDateTime> = HashSet::new();
+        for (pos, &(idx, value, date)) in dates.iter().enumerate() {
+            let prev = pos.checked_sub(1).map(|p| dates[p].2);
+            let violated = match check {
+                DateSequenceCheck::StrictlyAscending => prev.is_some_and(|p| date <= p),
+                DateSequenceCheck::StrictlyDescending => prev.is_some_and(|p| date >= p),
+                DateSequenceCheck::NoDuplicates => !seen.insert(date),
+                DateSequenceCheck::NoGapsLargerThan(max_gap) => {
+                    prev.is_some_and(|p| (date - p).abs() > max_gap)
+                }
+            };
+            if violated {
+                violations.push((idx, value.clone()));
+            }
+        }
+        Ok(violations)
+    }
+
+    /// Describe the sheet's columns as a JSON Schema (draft-07) object.
+    ///
+    /// Each column becomes a property typed from its `column_stats`: `"number"` if every
+    /// non-empty value is numeric, otherwise `"boolean"` or `"string"` with
+    /// `"format": "date"` if every value is a boolean or a date, and `"string"` for text,
+    /// mixed or all-empty columns. Columns with no blank or missing values are `required`.
+    ///
+    /// # Errors
+    ///
+    /// Currently infallible; the `Result` leaves room for future validation.
+    pub fn export_json_schema(&self) -> Result<serde_json::Value, ExcelDbError> {
+        let mut properties = serde_json::Map::new();
+        let mut required: Vec<serde_json::Value> = Vec::new();
+        for col in self.headers.iter().cloned() {
+            let stats = self.column_stats(&col)?;
+            let kind = if stats.non_empty > 0 && stats.numeric_count == stats.non_empty {
+                Some("number")
+            } else {
+                self.column_kind(&col)
+            };
+            let property = match kind {
+                Some("number") => serde_json::json!({ "type": "number" }),
+                Some("boolean") => serde_json::json!({ "type": "boolean" }),
+                Some("date") => serde_json::json!({ "type": "string", "format": "date" }),
+                _ => serde_json::json!({ "type": "string" }),
+            };
+            if stats.non_empty == stats.total_rows {
+                required.push(serde_json::Value::String(col.clone()));
+            }
+            properties.insert(col, property);
+        }
+        Ok(serde_json::json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": self.sheet_name,
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        }))
+    }
+
+    /// Names of columns where every row is blank or missing the column.
+    pub fn columns_with_all_nulls(&self) -> Vec<String> {
+        self.headers
+            .iter()
+            .filter(|col| {
+                self.data
+                    .iter()
+                    .all(|row| row.get(*col).is_none_or(|v| v.is_empty()))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Names of columns where at least one row is blank or missing the column.
+    pub fn columns_with_any_nulls(&self) -> Vec<String> {
+        self.headers
+            .iter()
+            .filter(|col| {
+                self.data
+                    .iter()
+                    .any(|row| row.get(*col).is_none_or(|v| v.is_empty()))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Remove every column reported by `columns_with_all_nulls`, save, and return their names.
+    ///
+    /// The file is only rewritten if at least one column was dropped.
+    ///
+    /// # Errors
+    ///
+    /// Propagates any error from `save_data`.
+    pub fn drop_columns_with_all_nulls(&mut self) -> Result<Vec<String>, ExcelDbError> {
+        self.ensure_writable()?;
+        let empty_columns = self.columns_with_all_nulls();
+        if empty_columns.is_empty() {
+            return Ok(empty_columns);
+        }
+        for row in self.data.iter_mut() {
+            for col in empty_columns.iter() {
+                row.shift_remove(col);
+            }
+        }
+        self.headers.retain(|h| !empty_columns.contains(h));
+        self.save_data()?;
+        Ok(empty_columns)
+    }
+
+    /// Number of rows whose value in `col` is missing or empty (blank or whitespace-only
+    /// text). A column that does not exist counts every row.
+    pub fn count_empty_cells(&self, col: &str) -> usize {
+        let col = self.column_key(col).map_or(col, String::as_str);
+        self.data
+            .iter()
+            .filter(|row| row.get(col).is_none_or(|v| v.is_empty()))
+            .count()
+    }
+
+    /// The `CellValue::kind` shared by every non-empty value of `col` (`"text"` if there are
+    /// none), or `None` if the values are of different kinds.
+    fn column_kind(&self, col: &str) -> Option<&'static str> {
+        let mut kinds = self
+            .data
+            .iter()
+            .filter_map(|row| row.get(col))
+            .filter(|v| !v.is_empty())
+            .map(|v| v.kind());
+        match kinds.next() {
+            Some(first) => kinds.all(|k| k == first).then_some(first),
+            None => Some("text"),
+        }
+    }
+
+    /// Summarize common data-quality problems in the loaded rows.
+    ///
+    /// This is a diagnostic only: it never fails and does not modify anything.
+    pub fn health_check(&self) -> HealthReport {
+        let headers = &self.headers;
+        let mut report = HealthReport::default();
+
+        for col in headers.iter() {
+            let nulls = self
+                .data
+                .iter()
+                .filter(|row| row.get(col).is_none_or(|v| v.is_empty()))
+                .count();
+            report.null_counts.insert(col.clone(), nulls);
+
+            let consistent = self.column_kind(col).is_some();
+            report.type_consistency.insert(col.clone(), consistent);
+
+            if col.trim().is_empty() {
+                report.empty_column_names.push(col.clone());
+            }
+        }
+
+        let mut seen: HashSet<Vec<(String, String)>> = HashSet::new();
+        for row in self.data.iter() {
+            let mut key: Vec<(String, String)> = row
+                .iter()
+                .map(|(k, v)| (k.clone(), format!("{:?}", v)))
+                .collect();
+            key.sort();
+            if !seen.insert(key) {
+                report.duplicate_row_count += 1;
+            }
+            if headers.iter().any(|h| !row.contains_key(h)) {
+                report.ragged_row_count += 1;
+            }
+        }
+        report
+    }
+
+    /// Expand a column holding JSON objects as text into one column per JSON key, then save.
+    ///
+    /// New columns are named `<prefix><key>` and appear in the order keys are first seen.
+    /// Rows whose cell is not a JSON object (including blank cells and `null`) get
+    /// `CellValue::Empty` in every new column, as do keys missing from a row's object.
+    /// Strings, numbers and booleans become `Text`, `Number` and `Bool` cells, `null` an empty
+    /// cell, and arrays and objects their JSON text. Returns the names of the new columns.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if no row has `column`.
+    /// - Propagates any error from `save_data`.
+    pub fn expand_json_column(
+        &mut self,
+        column: &str,
+        prefix: Option<&str>,
+        remove_original: bool,
+    ) -> Result<Vec<String>, ExcelDbError> {
+        self.ensure_writable()?;
+        let column = &self.ensure_column(column)?;
+        let prefix = prefix.unwrap_or("");
+
+        let parsed: Vec<Option<serde_json::Map<String, serde_json::Value>>> = self
+            .data
+            .iter()
+            .map(|row| match row.get(column) {
+                Some(CellValue::Text(s)) => match serde_json::from_str(s) {
+                    Ok(serde_json::Value::Object(map)) => Some(map),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+
+        let mut keys: Vec<String> = Vec::new();
+        for map in parsed.iter().flatten() {
+            for key in map.keys() {
+                if !keys.contains(key) {
+                    keys.push(key.clone());
+                }
+            }
+        }
+
+        for (row, map) in self.data.iter_mut().zip(parsed.iter()) {
+            for key in keys.iter() {
+                let value = map
+                    .as_ref()
+                    .and_then(|m| m.get(key))
+                    .map_or(CellValue::Empty, CellValue::from_json);
+                row.insert(format!("{}{}", prefix, key), value);
+            }
+            if remove_original {
+                row.shift_remove(column);
+            }
+        }
+        if remove_original {
+            self.headers.retain(|h| h != column);
+        }
+        self.save_data()?;
+        Ok(keys
+            .into_iter()
+            .map(|k| format!("{}{}", prefix, k))
+            .collect())
+    }
+
+    /// Count how many non-empty values exist in the specified column across all rows.
+    ///
+    /// # Errors
+    ///
+    /// `ColumnNotFound` if the sheet has no column `column_name`.
+    pub fn get_column_datas_number(&self, column_name: &str) -> Result<usize, ExcelDbError> {
+        let column_name = &self.ensure_column(column_name)?;
+        Ok(self
+            .data
+            .iter()
+            .filter(|row| row.get(column_name).is_some_and(|v| !v.is_empty()))
+            .count())
+    }
+
+    /// Number of data rows currently loaded.
+    pub fn row_count(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Whether the sheet's header row has a column named `col`.
+    pub fn has_column(&self, col: &str) -> bool {
+        self.column_key(col).is_some()
+    }
+
+    /// The formula (with its leading `=`) in column `col` (or an alias of it, matched as
+    /// `with_case_sensitivity` says) of row `row_index`, or `None` if the column or cell is
+    /// missing or the cell holds a plain value.
+    pub fn get_cell_formula(&self, col: &str, row_index: usize) -> Option<&str> {
+        let col = self.column_key(col)?;
+        match self.data.get(row_index)?.get(col)? {
+            CellValue::Formula { expression, .. } => Some(expression.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Add a new column with the given default value (or `CellValue::Empty` if `None`),
+    /// appended as the last column, then save.
+    ///
+    /// # Errors
+    ///
+    /// - `DuplicateColumn` if the sheet already has a column `column_name`.
+    /// - Propagates any I/O or spreadsheet errors from `save_data`.
+    pub fn add_column(
+        &mut self,
+        column_name: &str,
+        default_value: Option<CellValue>,
+    ) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        if self.has_column(column_name) {
+            return Err(ExcelDbError::DuplicateColumn(column_name.to_string()));
+        }
+        let default_val = default_value.unwrap_or(CellValue::Empty);
+        for row in self.data.iter_mut() {
+            row.insert(column_name.to_string(), default_val.clone());
+        }
+        self.headers.push(column_name.to_string());
+        self.save_data()?;
+        Ok(())
+    }
+
+    /// Column names of the loaded sheet, left to right as in the header row.
+    pub fn get_column_names(&self) -> Vec<String> {
+        self.headers.clone()
+    }
+
+    /// Add a column at position `pos` (0-based, counting from the left), filling every row
+    /// with `default` (or `CellValue::Empty`), then save. `pos` equal to the number of columns
+    /// appends, like `add_column`.
+    ///
+    /// # Errors
+    ///
+    /// - `DuplicateColumn` if the sheet already has a column `name`.
+    /// - `IndexOutOfBounds(pos)` if `pos` is greater than the number of columns.
+    /// - Propagates any error from `save_data`.
+    pub fn insert_column_at(
+        &mut self,
+        pos: usize,
+        name: &str,
+        default: Option<CellValue>,
+    ) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        if self.headers.iter().any(|h| h == name) {
+            return Err(ExcelDbError::DuplicateColumn(name.to_string()));
+        }
+        if pos > self.headers.len() {
+            return Err(ExcelDbError::IndexOutOfBounds(pos));
+        }
+        self.headers.insert(pos, name.to_string());
+        let default_val = default.unwrap_or(CellValue::Empty);
+        for row in self.data.iter_mut() {
+            let at = pos.min(row.len());
+            row.shift_insert(at, name.to_string(), default_val.clone());
+        }
+        self.save_data()?;
+        Ok(())
+    }
+
+    /// Add an auto-increment ID column, numbering the existing rows `1, 2, 3, ...`, then save.
+    ///
+    /// Afterwards every `insert` that does not supply the column gets the next ID. The
+    /// counter lives on this instance only; reopening the file starts without one.
+    ///
+    /// # Errors
+    ///
+    /// - `DuplicateColumn` if the sheet already has a column `column_name`.
+    /// - Propagates any error from `save_data`.
+    pub fn add_auto_increment_column(&mut self, column_name: &str) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        if self.headers.iter().any(|h| h == column_name) {
+            return Err(ExcelDbError::DuplicateColumn(column_name.to_string()));
+        }
+        self.headers.push(column_name.to_string());
+        for (idx, row) in self.data.iter_mut().enumerate() {
+            row.insert(column_name.to_string(), CellValue::Number((idx + 1) as f64));
+        }
+        self.auto_increment = Some(AutoIncrement {
+            column: column_name.to_string(),
+            last_value: self.data.len() as u64,
+        });
+        self.save_data()?;
+        Ok(())
+    }
+
+    /// Rename the column `old_name` to `new_name` in every row, keeping its position, then save.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if the sheet has no column `old_name`.
+    /// - `DuplicateColumn` if the sheet already has a column `new_name`.
+    /// - Propagates any error from `save_data`.
+    pub fn rename_column(&mut self, old_name: &str, new_name: &str) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        self.rename_in_memory(old_name, new_name)?;
+        self.save_data()?;
+        Ok(())
+    }
+
+    /// `rename_column` without saving.
+    fn rename_in_memory(&mut self, old_name: &str, new_name: &str) -> Result<(), ExcelDbError> {
+        let pos = self
+            .headers
+            .iter()
+            .position(|h| h == old_name)
+            .ok_or_else(|| ExcelDbError::ColumnNotFound(old_name.to_string()))?;
+        if self.headers.iter().any(|h| h == new_name) {
+            return Err(ExcelDbError::DuplicateColumn(new_name.to_string()));
+        }
+        self.headers[pos] = new_name.to_string();
+        for row in self.data.iter_mut() {
+            if let Some((idx, _, value)) = row.shift_remove_full(old_name) {
+                row.shift_insert(idx, new_name.to_string(), value);
+            }
+        }
+        if let Some(index) = self.indices.remove(old_name) {
+            self.indices.insert(new_name.to_string(), index);
+        }
+        if let Some(counter) = self
+            .auto_increment
+            .as_mut()
+            .filter(|c| c.column == old_name)
+        {
+            counter.column = new_name.to_string();
+        }
+        if self.auto_timestamp_column.as_deref() == Some(old_name) {
+            self.auto_timestamp_column = Some(new_name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Let `alias` stand for the column `canonical` in `select`, `update`, `delete`,
+    /// `get_column_value` and the other column-based lookups. If the sheet has a column
+    /// named `alias`, it is renamed to `canonical` in memory (the file changes on the next
+    /// save), and so is it whenever a sheet is loaded later.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if `alias` and `canonical` are the same.
+    /// - `DuplicateColumn` if the sheet has columns named both `alias` and `canonical`.
+    pub fn add_column_alias(&mut self, canonical: &str, alias: &str) -> Result<(), ExcelDbError> {
+        if alias == canonical {
+            return Err(ExcelDbError::InvalidParameter(format!(
+                "\"{}\" cannot be an alias of itself",
+                alias
+            )));
+        }
+        if self.headers.iter().any(|h| h == alias) {
+            self.rename_in_memory(alias, canonical)?;
+        }
+        self.aliases
+            .insert(alias.to_string(), canonical.to_string());
+        Ok(())
+    }
+
+    /// Stop treating `alias` as another name for its canonical column. Columns already
+    /// renamed keep their canonical name.
+    pub fn remove_column_alias(&mut self, alias: &str) {
+        self.aliases.remove(alias);
+    }
+
+    /// Every registered alias, mapped to its canonical column name.
+    pub fn list_aliases(&self) -> &HashMap<String, String> {
+        &self.aliases
+    }
+
+    /// Rearrange the columns into `order`, which must list every column exactly once.
+    /// The new order is written on the next save.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if `order` names a column the sheet does not have.
+    /// - `InvalidParameter` if `order` names a column more than once.
+    /// - `MissingColumnsInOrder` with the columns `order` leaves out.
+    pub fn reorder_columns(&mut self, order: &[&str]) -> Result<(), ExcelDbError> {
+        let mut seen: HashSet<&str> = HashSet::new();
+        for &name in order {
+            if !self.headers.iter().any(|h| h == name) {
+                return Err(ExcelDbError::ColumnNotFound(name.to_string()));
+            }
+            if !seen.insert(name) {
+                return Err(ExcelDbError::InvalidParameter(format!(
+                    "column \"{}\" appears more than once in the order",
+                    name
+                )));
+            }
+        }
+        let missing: Vec<String> = self
+            .headers
+            .iter()
+            .filter(|h| !seen.contains(h.as_str()))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            return Err(ExcelDbError::MissingColumnsInOrder(missing));
+        }
+
+        self.headers = order.iter().map(|s| s.to_string()).collect();
+        let headers = &self.headers;
+        for row in self.data.iter_mut() {
+            row.sort_by_cached_key(|key, _| headers.iter().position(|h| h == key));
+        }
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Remove a column from every row and save back to the file.
+    ///
+    /// # Errors
+    ///
+    /// - `ColumnNotFound` if the sheet has no column `column_name`.
+    /// - Propagates any I/O or spreadsheet errors from `save_data`.
+    pub fn remove_column(&mut self, column_name: &str) -> Result<(), ExcelDbError> {
+        self.ensure_writable()?;
+        let column_name = &self.ensure_column(column_name)?;
+        for row in self.data.iter_mut() {
+            row.shift_remove(column_name);
+        }
+        self.headers.retain(|h| h != column_name);
+        self.save_data()?;
+        Ok(())
+    }
+}
+
+/// Options for opening an `ExcelDatabase`, created with `ExcelDatabase::builder`.
+#[derive(Debug, Clone, Default)]
+pub struct ExcelDatabaseBuilder {
+    file_path: Option<PathBuf>,
+    sheet_name: Option<String>,
+    header_row: Option<u32>,
+    header_rows: Option<u32>,
+    merged_cells: MergedCellHandling,
+    duplicate_headers: DuplicateHeaderStrategy,
+    empty_cells: EmptyCellStrategy,
+    password: Option<String>,
+    write_password: Option<String>,
+    atomic_save: Option<bool>,
+}
+
+impl ExcelDatabaseBuilder {
+    /// The `.xlsx` file to open (required).
+    pub fn file_path<P: AsRef<Path>>(mut self, p: P) -> Self {
+        self.file_path = Some(p.as_ref().to_path_buf());
+        self
+    }
+
+    /// The sheet to load (default `"Sheet1"`).
+    pub fn sheet_name(mut self, s: &str) -> Self {
+        self.sheet_name = Some(s.to_string());
+        self
+    }
+
+    /// The 1-based row holding the header (default `1`). Rows above it, such as a title
+    /// block, are skipped when loading and left as they are when saving.
+    pub fn header_row(mut self, n: u32) -> Self {
+        self.header_row = Some(n);
+        self
+    }
+
+    /// How many rows, starting at `header_row`, the header spans (default `1`); the levels
+    /// are flattened into single names as `ExcelDatabase::load_with_multilevel_headers`
+    /// describes.
+    pub fn header_rows(mut self, n: u32) -> Self {
+        self.header_rows = Some(n);
+        self
+    }
+
+    /// What to do with merged cell ranges (default `MergedCellHandling::Ignore`).
+    pub fn merged_cells(mut self, handling: MergedCellHandling) -> Self {
+        self.merged_cells = handling;
+        self
+    }
+
+    /// What to do with columns sharing a header name (default
+    /// `DuplicateHeaderStrategy::Error`).
+    pub fn duplicate_headers(mut self, strategy: DuplicateHeaderStrategy) -> Self {
+        self.duplicate_headers = strategy;
+        self
+    }
+
+    /// What to do with empty cells (default `EmptyCellStrategy::Empty`).
+    pub fn empty_cell_strategy(mut self, s: EmptyCellStrategy) -> Self {
+        self.empty_cells = s;
+        self
+    }
+
+    /// Decrypt the workbook with `pwd`. Saves encrypt it again with the same password
+    /// unless `write_password` sets another one.
+    pub fn password(mut self, pwd: &str) -> Self {
+        self.password = Some(pwd.to_string());
+        self
+    }
+
+    /// Encrypt the workbook with `pwd` whenever it is saved.
+    pub fn write_password(mut self, pwd: &str) -> Self {
+        self.write_password = Some(pwd.to_string());
+        self
+    }
+
+    /// Enable or disable atomic saves (on by default). With atomic saves every write goes
+    /// to a temporary file that is flushed to disk and then replaces the original, so a
+    /// crash mid-write cannot corrupt it; turning them off writes the file in place, which
+    /// is slightly faster.
+    pub fn atomic_save(mut self, enabled: bool) -> Self {
+        self.atomic_save = Some(enabled);
+        self
+    }
+
+    /// Number of data rows below the configured header, taken from the sheet's highest row,
+    /// without building any `Row`s.
+    ///
+    /// # Errors
+    ///
+    /// Same as `sheet_metadata`.
+    pub fn row_count_on_disk(&self) -> Result<usize, ExcelDbError> {
+        Ok(self.sheet_metadata()?.row_count)
+    }
+
+    /// Row count and header names of the configured sheet, with the header read from the
+    /// configured `header_row` / `header_rows` and named as `build` would name it (see
+    /// `duplicate_headers`), without building any `Row`s.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if no file path was given or `header_row` / `header_rows` is zero.
+    /// - `EncryptionError` if the workbook cannot be decrypted with `password`.
+    /// - `SheetNotFound` if the sheet does not exist.
+    /// - `NoHeaders` if the sheet ends before the last header row.
+    /// - `MergedCells` if the sheet has merged cells and `MergedCellHandling::Error` is set.
+    /// - `DuplicateHeader` if two columns share a name and `DuplicateHeaderStrategy::Error`
+    ///   is set.
+    /// - Propagates I/O or spreadsheet errors from reading the workbook.
+    pub fn sheet_metadata(&self) -> Result<SheetMetadata, ExcelDbError> {
+        let (file_path, header_row, header_rows) = self.header_layout()?;
+        let sheet = self.sheet_name.as_deref().unwrap_or("Sheet1");
+        let book = read_xlsx(file_path, self.password.as_deref())?;
+        let worksheet = book
+            .get_sheet_by_name(sheet)
+            .ok_or_else(|| ExcelDbError::SheetNotFound(sheet.to_string()))?;
+        let merged = ExcelDatabase::merged_ranges(worksheet, sheet, self.merged_cells)?;
+        let columns = ExcelDatabase::read_header_columns(
+            worksheet,
+            sheet,
+            header_row,
+            header_rows,
+            self.duplicate_headers,
+            &merged,
+        )?;
+        let column_names: Vec<String> = columns.into_iter().map(|(_, name)| name).collect();
+        let first_data_row = header_row + header_rows;
+        Ok(SheetMetadata {
+            row_count: (worksheet.get_highest_row() + 1).saturating_sub(first_data_row) as usize,
+            column_count: column_names.len(),
+            column_names,
+        })
+    }
+
+    /// The configured file path, header row and number of header rows.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidParameter` if no file path was given or `header_row` / `header_rows` is zero.
+    fn header_layout(&self) -> Result<(&Path, u32, u32), ExcelDbError> {
+        let file_path = self
+            .file_path
+            .as_deref()
+            .ok_or_else(|| ExcelDbError::InvalidParameter("a file path is required".to_string()))?;
+        let header_row = self.header_row.unwrap_or(1);
+        if header_row == 0 {
+            return Err(ExcelDbError::InvalidParameter(
+                "header_row is 1-based and must be at least 1".to_string(),
+            ));
+        }
+        let header_rows = self.header_rows.unwrap_or(1);
+        if header_rows == 0 {
+            return Err(ExcelDbError::InvalidParameter(
+                "header_rows must be at least 1".to_string(),
+            ));
+        }
+        Ok((file_path, header_row, header_rows))
+    }
+
+    /// Open the file with the configured options.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if no file path was given or `header_row` / `header_rows` is zero.
+    /// - `EncryptionError` if the workbook cannot be decrypted with `password`.
+    /// - `SheetNotFound` if the sheet does not exist.
+    /// - `NoHeaders` if the sheet ends before the last header row.
+    /// - `MergedCells` if the sheet has merged cells and `MergedCellHandling::Error` is set.
+    /// - `DuplicateHeader` if two columns share a name and `DuplicateHeaderStrategy::Error`
+    ///   is set.
+    /// - Propagates I/O or spreadsheet errors from reading the workbook.
+    pub fn build(self) -> Result<ExcelDatabase, ExcelDbError> {
+        let (file_path, header_row, header_rows) = self.header_layout()?;
+        let mut db = ExcelDatabase::open(
+            file_path,
+            self.sheet_name.clone(),
+            header_row,
+            header_rows,
+            self.merged_cells,
+            self.duplicate_headers,
+            self.password.clone(),
+        )?;
+        db.write_password = self.write_password;
+        db.atomic_save = self.atomic_save.unwrap_or(true);
+        db.empty_cells = self.empty_cells;
+        db.data = self.empty_cells.apply(std::mem::take(&mut db.data));
+        Ok(db)
+    }
+
+    /// Open the configured sheet for row-by-row iteration with the same settings `build`
+    /// uses: header row(s), merged cells, duplicate headers and empty cells.
+    ///
+    /// The workbook itself is still parsed and held in memory in full, since the spreadsheet
+    /// library has no streaming reader; only the `Row`s are built lazily.
+    ///
+    /// # Errors
+    ///
+    /// Same as `build`. Errors from reading the workbook are returned here, never while
+    /// iterating.
+    pub fn open_lazy(&self) -> Result<LazyExcelDatabase, ExcelDbError> {
+        let (file_path, header_row, header_rows) = self.header_layout()?;
+        let sheet_name = self
+            .sheet_name
+            .clone()
+            .unwrap_or_else(|| "Sheet1".to_string());
+        let book = read_xlsx(file_path, self.password.as_deref())?;
+        let worksheet = book
+            .get_sheet_by_name(&sheet_name)
+            .ok_or_else(|| ExcelDbError::SheetNotFound(sheet_name.clone()))?;
+        let merged = ExcelDatabase::merged_ranges(worksheet, &sheet_name, self.merged_cells)?;
+        let columns = ExcelDatabase::read_header_columns(
+            worksheet,
+            &sheet_name,
+            header_row,
+            header_rows,
+            self.duplicate_headers,
+            &merged,
+        )?;
+        let last_row = worksheet.get_highest_row();
+        Ok(LazyExcelDatabase {
+            headers: columns.iter().map(|(_, name)| name.clone()).collect(),
+            columns,
+            merged,
+            empty_cells: self.empty_cells,
+            next_row: header_row + header_rows,
+            last_row,
+            sheet_name,
+            book,
+        })
+    }
+}
+
+/// Consumes the database, yielding its rows by value.
+impl IntoIterator for ExcelDatabase {
+    type Item = Row;
+    type IntoIter = std::vec::IntoIter<Row>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+/// A shared handle to one sheet of a `Workbook`, returned by `Workbook::table`. Lock it to
+/// use the sheet's `ExcelDatabase`; clones refer to the same sheet.
+pub type SheetHandle = Arc<Mutex<ExcelDatabase>>;
+
+/// Every sheet of one `.xlsx` file, each loaded as its own `ExcelDatabase` from a single
+/// read of the workbook.
+///
+/// Unlike separate databases opened on the same file, which each keep their own copy of the
+/// workbook and overwrite each other's saves, the sheets share one in-memory workbook.
+///
+/// Each sheet is reached through a `SheetHandle` from `table`; clones of a handle, and of
+/// the `Workbook` itself, refer to the same sheets. The sheets behave exactly like databases
+/// opened with `ExcelDatabase::new`: their write operations save immediately. `save_all`
+/// additionally writes any unsaved in-memory changes (e.g. made through `get_row_mut` or
+/// `filter_mut`, or with auto-save off) of all sheets at once.
+#[derive(Debug, Clone)]
+pub struct Workbook {
+    file_path: String,
+    /// The workbook shared by every sheet's database.
+    book: Arc<Mutex<Spreadsheet>>,
+    sheets: HashMap<String, SheetHandle>,
+    /// Whether `save_all` writes atomically (see `with_atomic_save`).
+    atomic_save: bool,
+}
+
+impl Workbook {
+    /// Read the workbook at `file_path` once and load each of its sheets.
+    ///
+    /// Sheets without a header row (completely empty sheets) are skipped.
+    ///
+    /// # Errors
+    ///
+    /// - `DuplicateHeader` if a sheet has two columns with the same header name.
+    /// - Propagates I/O or spreadsheet errors from reading the workbook.
+    pub fn open<P: AsRef<Path>>(file_path: P) -> Result<Self, ExcelDbError> {
+        let book = reader::xlsx::read(file_path.as_ref())?;
+        let names = book.get_sheet_names().to_vec();
+        let mut workbook = Self {
+            file_path: file_path.as_ref().to_string_lossy().to_string(),
+            book: Arc::new(Mutex::new(book)),
+            sheets: HashMap::new(),
+            atomic_save: true,
+        };
+        for name in names {
+            match workbook.load_sheet(&name) {
+                Ok(()) | Err(ExcelDbError::NoHeaders(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(workbook)
+    }
+
+    /// Load the sheet `name` from the shared workbook as a database using that workbook.
+    ///
+    /// # Errors
+    ///
+    /// - `SheetNotFound(name)` if the workbook has no such sheet.
+    /// - `NoHeaders(name)` if the sheet is empty.
+    /// - `DuplicateHeader` if two columns share a header name.
+    fn load_sheet(&mut self, name: &str) -> Result<(), ExcelDbError> {
+        let (headers, data) = {
+            let book = lock_book(&self.book)?;
+            let worksheet = book
+                .get_sheet_by_name(name)
+                .ok_or_else(|| ExcelDbError::SheetNotFound(name.to_string()))?;
+            ExcelDatabase::read_worksheet(
+                worksheet,
+                name,
+                1,
+                1,
+                MergedCellHandling::default(),
+                DuplicateHeaderStrategy::default(),
+            )?
+        };
+        let data = EmptyCellStrategy::default().apply(data);
+        let mut db =
+            ExcelDatabase::from_loaded(self.file_path.clone(), name.to_string(), headers, data, 1);
+        db.book = Some(SharedBook(Arc::clone(&self.book)));
+        self.sheets
+            .insert(name.to_string(), Arc::new(Mutex::new(db)));
+        Ok(())
+    }
+
+    /// A handle to the sheet named `name`. Every handle shares this workbook's in-memory
+    /// spreadsheet, so saving through one never overwrites another sheet's saved changes,
+    /// and every handle to the same sheet (including clones) sees the same rows.
+    ///
+    /// Sheets added after `open` (e.g. with `ExcelDatabase::add_sheet` on another handle)
+    /// are loaded on first use.
+    ///
+    /// # Errors
+    ///
+    /// - `SheetNotFound(name)` if the workbook has no such sheet.
+    /// - `NoHeaders(name)` if the sheet is empty.
+    pub fn table(&mut self, name: &str) -> Result<SheetHandle, ExcelDbError> {
+        if !self.sheets.contains_key(name) {
+            self.load_sheet(name)?;
+        }
+        self.sheet(name)
+            .ok_or_else(|| ExcelDbError::SheetNotFound(name.to_string()))
+    }
+
+    /// A handle to the sheet named `name`, if it is loaded (see `table`).
+    pub fn sheet(&self, name: &str) -> Option<SheetHandle> {
+        self.sheets.get(name).cloned()
+    }
+
+    /// Names of the loaded sheets, sorted alphabetically.
+    pub fn sheet_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.sheets.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Enable or disable atomic writes in `save_all` (on by default), as
+    /// `ExcelDatabaseBuilder::atomic_save` does for a single database.
+    pub fn with_atomic_save(mut self, enabled: bool) -> Self {
+        self.atomic_save = enabled;
+        self
+    }
+
+    /// Write every sheet with unsaved changes (see `ExcelDatabase::has_unsaved_changes`)
+    /// to the file, writing the workbook only once. Sheets inside an open transaction are
+    /// left alone until it is committed.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` if a sheet's handle is locked, e.g. by a guard still held by the
+    ///   caller.
+    /// - `Poisoned` if a panic occurred while the workbook or a sheet was locked.
+    /// - `ReadOnly` if a changed sheet was opened read-only.
+    /// - `SheetNotFound` if a changed sheet has since been removed from the workbook.
+    /// - Propagates I/O or spreadsheet errors from writing the workbook.
+    pub fn save_all(&mut self) -> Result<(), ExcelDbError> {
+        let mut dirty: Vec<MutexGuard<'_, ExcelDatabase>> = Vec::new();
+        for (name, handle) in self.sheets.iter() {
+            let db = match handle.try_lock() {
+                Ok(db) => db,
+                Err(TryLockError::Poisoned(_)) => return Err(ExcelDbError::Poisoned),
+                Err(TryLockError::WouldBlock) => {
+                    return Err(ExcelDbError::InvalidParameter(format!(
+                        "sheet \"{}\" is locked through another handle",
+                        name
+                    )))
+                }
+            };
+            if !db.in_transaction() && db.has_unsaved_changes() {
+                dirty.push(db);
+            }
+        }
+        if dirty.is_empty() {
+            return Ok(());
+        }
+        dirty.sort_by(|a, b| a.sheet_name.cmp(&b.sheet_name));
+
+        let mut book = lock_book(&self.book)?;
+        for db in dirty.iter_mut() {
+            db.ensure_writable()?;
+            db.sync_headers();
+            db.rebuild_indices();
+            db.replace_sheet(&mut book)?;
+        }
+        write_file(Path::new(&self.file_path), self.atomic_save, |path| {
+            write_xlsx(&book, path, None)
+        })?;
+
+        for mut db in dirty {
+            db.finish_write();
+        }
+        Ok(())
+    }
+}
+
+/// A sheet read one row at a time, created by `ExcelDatabaseBuilder::open_lazy` or
+/// `ExcelDatabase::open_lazy`.
+///
+/// Iterating yields each data row below the header as a `Row`, building it only when it is
+/// requested, so large sheets can be scanned without holding every `Row` in memory. The
+/// parsed workbook itself is held in full for the iterator's lifetime: this saves the
+/// memory of the `Row`s, not of the file.
+#[derive(Debug, Clone)]
+pub struct LazyExcelDatabase {
+    book: Spreadsheet,
+    sheet_name: String,
+    headers: Vec<String>,
+    /// Zero-based sheet column of each header.
+    columns: Vec<(usize, String)>,
+    merged: Vec<CellRange>,
+    empty_cells: EmptyCellStrategy,
+    /// Excel row number (1-based) of the next row to yield.
+    next_row: u32,
+    last_row: u32,
+}
+
+impl LazyExcelDatabase {
+    /// Column names from the sheet's header row(s).
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// Only the rows for which `pred` returns `true`. Errors are passed through.
+    pub fn filter_lazy<F: Fn(&Row) -> bool>(
+        self,
+        pred: F,
+    ) -> impl Iterator<Item = Result<Row, ExcelDbError>> {
+        self.filter(move |row| row.as_ref().map_or(true, &pred))
+    }
+}
+
+impl Iterator for LazyExcelDatabase {
+    type Item = Result<Row, ExcelDbError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_row > self.last_row {
+            return None;
+        }
+        let Some(worksheet) = self.book.get_sheet_by_name(&self.sheet_name) else {
+            self.next_row = self.last_row + 1;
+            return Some(Err(ExcelDbError::SheetNotFound(self.sheet_name.clone())));
+        };
+        while self.next_row <= self.last_row {
+            let excel_row = self.next_row;
+            self.next_row += 1;
+            let row: Row = self
+                .columns
+                .iter()
+                .map(|(col, header)| {
+                    let value = ExcelDatabase::read_cell(
+                        worksheet,
+                        &self.merged,
+                        *col as u32 + 1,
+                        excel_row,
+                    );
+                    (header.clone(), value)
+                })
+                .collect();
+            if let Some(row) = self.empty_cells.apply_to_row(row) {
+                return Some(Ok(row));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.last_row + 1).saturating_sub(self.next_row) as usize;
+        match self.empty_cells {
+            EmptyCellStrategy::SkipRow => (0, Some(remaining)),
+            _ => (remaining, Some(remaining)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `from_rows` database with columns `id` and `name`, one row per name.
+    fn people(names: &[&str]) -> ExcelDatabase {
+        let rows = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mut row = Row::new();
+                row.insert("id".to_string(), CellValue::Number(i as f64 + 1.0));
+                row.insert("name".to_string(), CellValue::Text(name.to_string()));
+                row
+            })
+            .collect();
+        ExcelDatabase::from_rows(rows, vec!["id".to_string(), "name".to_string()])
+    }
+
+    fn query(column: &str, value: CellValue) -> Row {
+        let mut row = Row::new();
+        row.insert(column.to_string(), value);
+        row
+    }
+
+    /// A path in the temporary directory that is unique to this test run.
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("excel_database_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn multilevel_header_survives_save_and_reload() {
+        let path = temp_path("multilevel.csv");
+        fs::write(&path, "Q1,Q1\nRevenue,Profit\n1,2\n3,4\n").unwrap();
+        let mut db = ExcelDatabase::load_with_multilevel_headers(&path, None, 2).unwrap();
+        assert_eq!(db.get_column_names(), ["Q1_Revenue", "Q1_Profit"]);
+
+        let mut row = Row::new();
+        row.insert("Q1_Revenue".to_string(), CellValue::Text("5".to_string()));
+        row.insert("Q1_Profit".to_string(), CellValue::Text("6".to_string()));
+        db.insert(row).unwrap();
+        db.reload().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(db.get_column_names(), ["Q1_Revenue", "Q1_Profit"]);
+        assert_eq!(db.count(None), 3);
+        assert_eq!(
+            db.get_cell_by_coordinate("A", 2),
+            Some(&CellValue::Text("1".to_string()))
+        );
+    }
+
+    #[test]
+    fn repeated_headers_are_rejected_by_default_and_blank_ones_named() {
+        let path = temp_path("headers.csv");
+        fs::write(&path, "name,,name,\na,b,c,d\n").unwrap();
+        let by_default = ExcelDatabase::new(&path, None);
+        let load = |strategy| {
+            ExcelDatabase::builder()
+                .file_path(&path)
+                .duplicate_headers(strategy)
+                .build()
+                .map(|db| (db.get_column_names(), db.to_rows()[0].clone()))
+        };
+        let renamed = load(DuplicateHeaderStrategy::RenameWithSuffix);
+        let first = load(DuplicateHeaderStrategy::KeepFirst);
+        let last = load(DuplicateHeaderStrategy::KeepLast);
+        let error = load(DuplicateHeaderStrategy::Error);
+        fs::remove_file(&path).unwrap();
+
+        match by_default {
+            Err(ExcelDbError::DuplicateHeader {
+                sheet,
+                column,
+                positions,
+            }) => {
+                assert_eq!((sheet.as_str(), column.as_str()), ("Sheet1", "name"));
+                assert_eq!(positions, [0, 2]);
+            }
+            other => panic!("expected DuplicateHeader, got {:?}", other),
+        }
+        assert!(matches!(error, Err(ExcelDbError::DuplicateHeader { .. })));
+        let (names, _) = renamed.unwrap();
+        assert_eq!(names, ["name", "Column_2", "name_2", "Column_4"]);
+        let text = |s: &str| CellValue::Text(s.to_string());
+        let (names, row) = first.unwrap();
+        assert_eq!(names, ["name", "Column_2", "Column_4"]);
+        assert_eq!(row["name"], text("a"));
+        let (names, row) = last.unwrap();
+        assert_eq!(names, ["Column_2", "name", "Column_4"]);
+        assert_eq!(row["name"], text("c"));
+    }
+
+    #[test]
+    fn ordering_agrees_with_equality() {
+        let number = CellValue::Number(10.0);
+        let text = CellValue::Text("10".to_string());
+        assert_ne!(number, text);
+        assert_eq!(number.partial_cmp(&text), None);
+        assert_eq!(number.compare_loose(&text), Some(Ordering::Equal));
+
+        let nan = CellValue::Number(f64::NAN);
+        assert_eq!(nan, nan.clone());
+        assert_eq!(nan.partial_cmp(&nan), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn cell_formula_is_found_by_alias() {
+        let mut db = people(&["Ann"]);
+        let formula = CellValue::Formula {
+            expression: "=1+1".to_string(),
+            cached_result: Box::new(CellValue::Number(2.0)),
+        };
+        db.get_row_mut(0)
+            .unwrap()
+            .insert("name".to_string(), formula);
+        db.add_column_alias("name", "full_name").unwrap();
+        assert_eq!(db.get_cell_formula("full_name", 0), Some("=1+1"));
+    }
+
+    #[test]
+    fn missing_values_load_as_empty() {
+        let path = temp_path("empty.csv");
+        fs::write(&path, "name,note\nAnn,\n").unwrap();
+        let db = ExcelDatabase::new(&path, None);
+        fs::remove_file(&path).unwrap();
+
+        let db = db.unwrap();
+        assert_eq!(db.get_cell_by_coordinate("B", 2), Some(&CellValue::Empty));
+        assert_eq!(
+            CellValue::Empty.partial_cmp(&CellValue::Empty),
+            Some(Ordering::Equal)
+        );
+    }
+
+    #[test]
+    fn skip_row_skips_only_missing_values_and_stays_writable() {
+        let path = temp_path("skip_row.csv");
+        fs::write(&path, "name,note\nAnn,\nBob, \n").unwrap();
+        let db = ExcelDatabase::builder()
+            .file_path(&path)
+            .empty_cell_strategy(EmptyCellStrategy::SkipRow)
+            .build();
+        let mut db = db.unwrap();
+        let loaded = db.count(None);
+        let bob_note = db.get_cell_by_coordinate("B", 2).cloned();
+        let inserted = db.insert(query("name", CellValue::Text("Cy".to_string())));
+        let saved = fs::read_to_string(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, 1);
+        assert_eq!(bob_note, Some(CellValue::Text(" ".to_string())));
+        assert!(!db.is_read_only());
+        assert!(inserted.is_ok());
+        assert_eq!(saved.unwrap().lines().count(), 3);
+    }
+
+    #[test]
+    fn read_only_mode_rejects_writes_and_still_reads() {
+        let path = temp_path("read_only.csv");
+        fs::write(&path, "id,name\n1,Ann\n").unwrap();
+        let db = ExcelDatabase::open_read_only(&path, None);
+        let mut db = db.unwrap();
+        let ann = query("name", CellValue::Text("Ann".to_string()));
+        let is_read_only = |r: Result<(), ExcelDbError>| matches!(r, Err(ExcelDbError::ReadOnly));
+
+        assert!(is_read_only(db.insert(ann.clone())));
+        assert!(is_read_only(db.update(&ann, &ann).map(|_| ())));
+        assert!(is_read_only(db.delete(&ann)));
+        assert!(is_read_only(db.add_column("email", None)));
+        assert!(is_read_only(db.remove_column("name")));
+        assert!(is_read_only(db.save()));
+        let on_disk = fs::read_to_string(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(on_disk.unwrap(), "id,name\n1,Ann\n");
+        assert_eq!(db.row_count(), 1);
+        assert_eq!(db.select(Some(&ann)).map(|rows| rows.len()), Some(1));
+        let id = db.get_column_value("name", &CellValue::Text("Ann".to_string()), "id");
+        assert_eq!(id.unwrap(), Some(CellValue::Text("1".to_string())));
+    }
+
+    #[test]
+    fn edits_survive_save_and_reload() {
+        let path = temp_path("round_trip.csv");
+        fs::write(&path, "id,name\n1,Ann\n2,Bob\n").unwrap();
+        let mut db = ExcelDatabase::new(&path, None).unwrap();
+        let name = |n: &str| CellValue::Text(n.to_string());
+        let mut cid = query("id", CellValue::Text("3".to_string()));
+        cid.insert("name".to_string(), name("Cid"));
+
+        db.insert(cid).unwrap();
+        db.update(&query("name", name("Bob")), &query("name", name("Rob")))
+            .unwrap();
+        db.delete(&query("name", name("Ann"))).unwrap();
+        let reopened = ExcelDatabase::new(&path, None);
+        db.reload().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let reopened = reopened.unwrap();
+        assert_eq!(reopened.get_column_names(), ["id", "name"]);
+        assert_eq!(reopened.to_rows(), db.to_rows());
+        let names: Vec<_> = db.to_rows().iter().map(|row| row["name"].clone()).collect();
+        assert_eq!(names, [name("Rob"), name("Cid")]);
+    }
+
+    #[test]
+    fn change_log_records_every_row_mutation() {
+        let mut db = people(&["Ann", "Bob", "Cid"]);
+        db.set_auto_save(false);
+        db.enable_change_tracking();
+        let ann = db.get_row(0).cloned().unwrap();
+        let bob = db.get_row(1).cloned().unwrap();
+
+        db.delete_at(0).unwrap();
+        let name = |n: &str| CellValue::Text(n.to_string());
+        db.find_replace(&name("Bob"), name("Rob")).unwrap();
+        db.clear().unwrap();
+
+        let log = db.pending_changes().unwrap();
+        assert_eq!(log.len(), 4);
+        assert_eq!(log[0], Change::Delete(ann));
+        assert!(matches!(&log[1], Change::Update { before, .. } if *before == bob));
+        assert!(matches!(&log[3], Change::Delete(row) if row["name"] == name("Cid")));
+    }
+
+    #[test]
+    fn unsaved_changes_are_cleared_by_a_save() {
+        let path = temp_path("unsaved.csv");
+        fs::write(&path, "id,name\n1,Ann\n").unwrap();
+        let mut db = ExcelDatabase::new(&path, None).unwrap();
+        assert!(!db.has_unsaved_changes());
+
+        db.set_auto_save(false);
+        db.delete_at(0).unwrap();
+        assert!(db.has_unsaved_changes());
+        let saved = db.save();
+        fs::remove_file(&path).unwrap();
+        saved.unwrap();
+        assert!(!db.has_unsaved_changes());
+    }
+
+    #[test]
+    fn cloned_database_gets_its_own_workbook() {
+        let book = SharedBook::new(umya_spreadsheet::new_file_empty_worksheet());
+        let copy = book.clone();
+        assert!(!Arc::ptr_eq(&book.0, &copy.0));
+    }
+
+    #[test]
+    fn csv_saves_are_backed_up_and_leave_no_temp_file() {
+        let path = temp_path("backup.csv");
+        let backups = temp_path("backups");
+        fs::write(&path, "id,name\n1,Ann\n").unwrap();
+        let mut db = ExcelDatabase::new(&path, None).unwrap();
+        db.set_auto_backup(Some(backups.clone()));
+        let deleted = db.delete_at(0);
+        let backed_up = fs::read_dir(&backups).map(|dir| dir.count());
+        let tmp_left = Path::new(&format!("{}.tmp", path.display())).exists();
+        let saved = fs::read_to_string(&path);
+        fs::remove_file(&path).unwrap();
+        fs::remove_dir_all(&backups).unwrap();
+
+        deleted.unwrap();
+        assert_eq!(backed_up.unwrap(), 1);
+        assert!(!tmp_left);
+        assert_eq!(saved.unwrap(), "id,name\n");
+    }
+
+    #[test]
+    fn back_to_back_backups_do_not_overwrite_each_other() {
+        let path = temp_path("backup_twice.csv");
+        let backups = temp_path("backups_twice");
+        fs::write(&path, "id,name\n1,Ann\n").unwrap();
+        let mut db = ExcelDatabase::new(&path, None).unwrap();
+        let first = db.save_with_backup(&backups);
+        let second = db.save_with_backup(&backups);
+        let backed_up = fs::read_dir(&backups).map(|dir| dir.count());
+        fs::remove_file(&path).unwrap();
+        fs::remove_dir_all(&backups).unwrap();
+
+        assert_ne!(first.unwrap(), second.unwrap());
+        assert_eq!(backed_up.unwrap(), 2);
+    }
+
+    #[test]
+    fn tsv_round_trip_keeps_empty_sheets_and_blank_rows() {
+        let path = temp_path("round_trip.tsv");
+        let mut empty = ExcelDatabase::from_rows(Vec::new(), Vec::new());
+        empty.export_tsv(&path).unwrap();
+        let imported = empty.import_tsv(&path);
+
+        let mut row = Row::new();
+        row.insert("note".to_string(), CellValue::Text(String::new()));
+        let mut blank = ExcelDatabase::from_rows(vec![row], vec!["note".to_string()]);
+        blank.export_tsv(&path).unwrap();
+        let blank_imported = blank.import_tsv(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(imported.unwrap(), 0);
+        assert_eq!(blank_imported.unwrap(), 1);
+        assert_eq!(blank.get_column_names(), ["note"]);
+    }
+
+    #[test]
+    fn index_follows_rows_edited_through_get_row_mut() {
+        let mut db = people(&["Ann", "Bob"]);
+        db.build_index("name").unwrap();
+        db.get_row_mut(0)
+            .unwrap()
+            .insert("name".to_string(), CellValue::Text("Cid".to_string()));
+
+        let found = db.select(Some(&query("name", CellValue::Text("Cid".to_string()))));
+        assert_eq!(found.map(|rows| rows.len()), Some(1));
+        assert!(db
+            .select(Some(&query("name", CellValue::Text("Ann".to_string()))))
+            .is_none());
+        let id = db.get_column_value("name", &CellValue::Text("Cid".to_string()), "id");
+        assert_eq!(id.unwrap(), Some(CellValue::Number(1.0)));
+    }
+
+    #[test]
+    fn unicode_matching_ignores_case_and_normalisation_form() {
+        let db = people(&["Caf\u{e9}", "CAFE\u{301}", "Cafe", "\u{6771}\u{4eac}"]);
+
+        let found = db
+            .select_matching_unicode("name", "^caf\u{e9}$")
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.len(), 2);
+        let letters = db
+            .select_matching_unicode("name", r"^\p{Han}+$")
+            .unwrap()
+            .unwrap();
+        assert_eq!(letters.len(), 1);
+        let ci = db
+            .select_matching_ci("name", "^caf\u{e9}$")
+            .unwrap()
+            .unwrap();
+        assert_eq!(ci.len(), 1);
+    }
+
+    #[test]
+    fn error_messages_are_stable() {
+        let cases = [
+            (
+                ExcelDbError::SheetNotFound("Q1".to_string()),
+                "Sheet \"Q1\" not found",
+            ),
+            (
+                ExcelDbError::ReadOnly,
+                "Database was opened in read-only mode",
+            ),
+            (
+                ExcelDbError::ColumnNotFound("age".to_string()),
+                "Column \"age\" not found",
+            ),
+            (
+                ExcelDbError::IndexOutOfBounds(7),
+                "Row index 7 is out of bounds",
+            ),
+            (
+                ExcelDbError::NotANumericColumn("name".to_string()),
+                "Column \"name\" has no numeric values",
+            ),
+            (
+                ExcelDbError::WriteError("disk full".to_string()),
+                "Spreadsheet writing error: disk full",
+            ),
+            (
+                "abc".parse::<f64>().unwrap_err().into(),
+                "Invalid number: invalid float literal",
+            ),
+            (
+                ExcelDbError::NoFilePath,
+                "Database has no file; it was created with from_rows",
+            ),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(error.to_string(), expected);
+        }
+
+        let error = ExcelDbError::SheetNotFound("Q1".to_string()).with_context("loading a.xlsx");
+        assert_eq!(error.to_string(), "loading a.xlsx");
+        let source = std::error::Error::source(&error).map(ToString::to_string);
+        assert_eq!(source.as_deref(), Some("Sheet \"Q1\" not found"));
+    }
+
+    #[test]
+    fn aggregators_can_report_unparsable_numbers() {
+        let mut db = people(&["Ann", "Bob"]);
+        db.rows_mut()[1].insert("id".to_string(), CellValue::Text("n/a".to_string()));
+        let total = |rows: &Vec<Row>| -> Result<f64, ExcelDbError> {
+            let mut sum = 0.0;
+            for row in rows {
+                sum += row
+                    .get("id")
+                    .map_or(Ok(0.0), |v| v.to_string().parse::<f64>())?;
+            }
+            Ok(sum)
+        };
+        let by_name = db.try_group_by_aggregate("name", total);
+        assert!(matches!(by_name, Err(ExcelDbError::ParseFloatError(_))));
+        db.rows_mut()[1].insert("id".to_string(), CellValue::Text("2".to_string()));
+        assert_eq!(
+            db.try_group_by_aggregate("name", total).unwrap()["Bob"],
+            2.0
+        );
+    }
+
+    #[test]
+    fn typed_rows_round_trip_through_serde() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Person {
+            name: String,
+            age: u32,
+        }
+        let headers = vec!["name".to_string(), "age".to_string()];
+        let mut db = ExcelDatabase::from_rows(Vec::new(), headers);
+        let ann = Person {
+            name: "Ann".to_string(),
+            age: 31,
+        };
+        db.insert_typed(&ann).unwrap();
+
+        assert_eq!(db.to_rows()[0]["age"], CellValue::Number(31.0));
+        let people: Vec<Person> = db.select_as(None).unwrap();
+        assert_eq!(people, [ann]);
+    }
+
+    #[test]
+    fn insert_from_round_trips_fractional_fields() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Person {
+            name: String,
+            age: f64,
+        }
+        let headers = vec!["name".to_string(), "age".to_string()];
+        let mut db = ExcelDatabase::from_rows(Vec::new(), headers);
+        let bob = Person {
+            name: "Bob".to_string(),
+            age: 42.5,
+        };
+        db.insert_from(&bob).unwrap();
+
+        let people: Vec<Person> = db
+            .select_as(Some(&query("age", CellValue::Number(42.5))))
+            .unwrap();
+        assert_eq!(people, [bob]);
+        assert!(db.insert_from(&"not a struct").is_err());
+    }
+
+    #[test]
+    fn rows_are_selected_by_value_type() {
+        let mut db = people(&["Ann", "Bob", "Cid", "Dee"]);
+        let rows = db.rows_mut();
+        rows[1].insert("name".to_string(), CellValue::Number(7.0));
+        rows[2].insert("name".to_string(), CellValue::Text("#DIV/0!".to_string()));
+        rows[3].insert("name".to_string(), CellValue::Empty);
+
+        let count = |kind| db.select_by_value_type("name", kind).unwrap().len();
+        assert_eq!(count(CellValueType::Text), 2);
+        assert_eq!(count(CellValueType::Number), 1);
+        assert_eq!(count(CellValueType::Empty), 1);
+        assert_eq!(count(CellValueType::Bool), 0);
+        assert!(db.select_by_value_type("age", CellValueType::Text).is_err());
+    }
+
+    #[test]
+    fn validator_violations_are_reported_in_registration_order() {
+        let mut db = people(&["Ann"]);
+        db.add_column_validator("name", |v| !v.is_empty());
+        db.add_column_validator("id", |v| matches!(v, CellValue::Number(_)));
+        let mut row = query("name", CellValue::Empty);
+        row.insert("id".to_string(), CellValue::Text("x".to_string()));
+
+        match db.insert(row) {
+            Err(ExcelDbError::ValidationError(violations)) => assert_eq!(
+                violations,
+                [
+                    "row 0: column \"name\" rejected \"\"",
+                    "row 0: column \"id\" rejected \"x\"",
+                ]
+            ),
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn count_exists_and_select_all_agree_with_select() {
+        let db = people(&["Ann", "Bob", "Ann"]);
+        let ann = query("name", CellValue::Text("Ann".to_string()));
+        let cid = query("name", CellValue::Text("Cid".to_string()));
+
+        assert_eq!(db.count(Some(&ann)), 2);
+        assert_eq!(db.count(None), 3);
+        assert!(db.exists(&ann));
+        assert!(!db.exists(&cid));
+        assert_eq!(db.select_all(), db.to_rows());
+        assert_eq!(db.select(Some(&Row::new())), Some(db.select_all()));
+        assert_eq!(db.select(None), Some(db.select_all()));
+        assert_eq!(db.count(Some(&Row::new())), 3);
+        assert!(db.exists(&Row::new()));
+    }
+
+    #[test]
+    fn closure_predicates_select_update_and_delete() {
+        let mut db = people(&["Ann", "Bob", "Cid"]);
+        db.set_auto_save(false);
+        let id_above = |min: f64| move |row: &Row| matches!(row.get("id"), Some(CellValue::Number(n)) if *n > min);
+
+        assert_eq!(db.select_where(id_above(1.0)).len(), 2);
+        assert!(db.select_where(id_above(3.0)).is_empty());
+        let updated = db.update_where(id_above(2.0), &query("name", CellValue::Text("Cy".into())));
+        assert_eq!(updated.unwrap(), 1);
+        assert_eq!(db.delete_where(id_above(1.0)).unwrap(), 2);
+        assert_eq!(db.select_all(), people(&["Ann"]).select_all());
+    }
+
+    #[test]
+    fn password_protected_workbooks_open_only_with_the_right_password() {
+        let path = temp_path("protected.xlsx");
+        let name = |n: &str| query("name", CellValue::Text(n.to_string()));
+        let mut db = ExcelDatabase::create(&path, None, vec!["name".to_string()]).unwrap();
+        db.insert(name("Ann")).unwrap();
+        let book = reader::xlsx::read(&path).unwrap();
+        writer::xlsx::write_with_password(&book, &path, "secret").unwrap();
+        let open = |pwd: &str| {
+            ExcelDatabase::builder()
+                .file_path(&path)
+                .password(pwd)
+                .build()
+        };
+
+        let wrong = open("guess");
+        let without = ExcelDatabase::new(&path, None);
+        let mut db = open("secret").unwrap();
+        let resaved = db.insert(name("Bob"));
+        let reopened = open("secret");
+        let mut db = ExcelDatabase::builder()
+            .file_path(&path)
+            .password("secret")
+            .write_password("changed")
+            .build()
+            .unwrap();
+        let rekeyed = db.insert(name("Cid"));
+        let old_password = open("secret");
+        let new_password = open("changed");
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(wrong, Err(ExcelDbError::EncryptionError(_))));
+        assert!(without.is_err());
+        resaved.unwrap();
+        assert_eq!(reopened.unwrap().count(None), 2);
+        rekeyed.unwrap();
+        assert!(matches!(
+            old_password,
+            Err(ExcelDbError::EncryptionError(_))
+        ));
+        assert_eq!(new_password.unwrap().count(None), 3);
+    }
+
+    #[test]
+    fn every_update_path_checks_the_schema() {
+        let mut db = people(&["Ann", "Bob"]);
+        db.set_auto_save(false);
+        db.set_schema(Schema::new().require("id", ColumnType::Number).build());
+        let ann = query("name", CellValue::Text("Ann".to_string()));
+        let bad_id = query("id", CellValue::Text("one".to_string()));
+        let is_invalid =
+            |r: Result<bool, ExcelDbError>| matches!(r, Err(ExcelDbError::ValidationError(_)));
+
+        assert!(is_invalid(db.update_first_matching(&ann, &bad_id)));
+        assert!(is_invalid(db.merge_row_fields(
+            "name",
+            &CellValue::Text("Bob".to_string()),
+            bad_id.clone(),
+            true
+        )));
+        assert!(matches!(
+            db.update(&ann, &bad_id),
+            Err(ExcelDbError::ValidationError(_))
+        ));
+        assert_eq!(db.select_all(), people(&["Ann", "Bob"]).select_all());
+        assert!(db.validate_all().is_ok());
+        let good_id = query("id", CellValue::Number(7.0));
+        assert!(db.update_first_matching(&ann, &good_id).unwrap());
+        assert_eq!(db.to_rows()[0]["id"], CellValue::Number(7.0));
+    }
+
+    #[test]
+    fn column_validators_run_on_every_update_path() {
+        let mut db = people(&["Ann", "Bob"]);
+        db.set_auto_save(false);
+        db.add_column_validator("name", |v| !v.is_empty());
+        let ann = query("name", CellValue::Text("Ann".to_string()));
+        let blank = query("name", CellValue::Empty);
+        let rejected = |r: Result<(), ExcelDbError>| matches!(r, Err(ExcelDbError::ValidationError(v)) if v[0].contains("\"name\""));
+
+        assert!(rejected(db.insert(blank.clone())));
+        assert!(rejected(db.update(&ann, &blank).map(|_| ())));
+        assert!(rejected(db.update_at(0, blank.clone())));
+        assert!(rejected(db.update_first_matching(&ann, &blank).map(|_| ())));
+        let bob = CellValue::Text("Bob".to_string());
+        assert!(rejected(
+            db.merge_row_fields("name", &bob, blank.clone(), true)
+                .map(|_| ())
+        ));
+        assert_eq!(db.select_all(), people(&["Ann", "Bob"]).select_all());
+        assert_eq!(db.validate_column("name").unwrap(), 0);
+
+        db.remove_column_validator("name");
+        assert!(db.update_first_matching(&ann, &blank).unwrap());
+    }
+
+    #[test]
+    fn crud_works_in_memory_and_saving_reports_no_file() {
+        let mut db = people(&["Ann", "Bob"]);
+        let name = |n: &str| query("name", CellValue::Text(n.to_string()));
+        let mut cid = name("Cid");
+        cid.insert("id".to_string(), CellValue::Number(3.0));
+
+        assert!(!db.is_auto_save());
+        db.insert(cid.clone()).unwrap();
+        assert_eq!(db.select(Some(&name("Cid"))), Some(vec![cid]));
+        assert_eq!(db.update(&name("Bob"), &name("Rob")).unwrap(), 1);
+        db.delete(&name("Ann")).unwrap();
+        db.add_column("email", None).unwrap();
+        let names: Vec<_> = db.iter().map(|row| row["name"].to_string()).collect();
+        assert_eq!(names, ["Rob", "Cid"]);
+        assert!(db.has_unsaved_changes());
+
+        assert!(matches!(db.save(), Err(ExcelDbError::NoFilePath)));
+        assert!(matches!(db.commit(), Err(ExcelDbError::NoFilePath)));
+        db.set_auto_save(true);
+        assert!(matches!(
+            db.insert(name("Dee")),
+            Err(ExcelDbError::NoFilePath)
+        ));
+    }
+
+    #[test]
+    fn projections_lookups_and_merges_resolve_column_names() {
+        let mut db = people(&["Ann", "Bob"]).with_case_sensitivity(CaseSensitivity::Insensitive);
+        db.add_column_alias("name", "full_name").unwrap();
+        let ann = CellValue::Text("Ann".to_string());
+
+        let projected = db.select_columns(None, &["NAME", "Id"]).unwrap();
+        let looked_up = db.get_column_values_all("Name", &ann, "ID").unwrap();
+        let mut extra = query("full_name", CellValue::Text("Ann Lee".to_string()));
+        extra.insert("city".to_string(), CellValue::Text("Oslo".to_string()));
+        let merged = db.merge_row_fields("NAME", &ann, extra, true).unwrap();
+        let ann_row = db
+            .select(Some(&query("id", CellValue::Number(1.0))))
+            .unwrap();
+
+        assert_eq!(projected[0].keys().collect::<Vec<_>>(), ["name", "id"]);
+        assert_eq!(projected[1]["name"], CellValue::Text("Bob".to_string()));
+        assert_eq!(looked_up, [CellValue::Number(1.0)]);
+        assert!(merged);
+        assert_eq!(ann_row[0]["name"], CellValue::Text("Ann Lee".to_string()));
+        assert_eq!(ann_row[0]["city"], CellValue::Text("Oslo".to_string()));
+        assert!(!ann_row[0].contains_key("full_name"));
+    }
+
+    #[test]
+    fn column_values_lists_every_match_and_rejects_unknown_columns() {
+        let db = people(&["Ann", "Bob", "Ann", "Ann"]);
+        let ann = CellValue::Text("Ann".to_string());
+
+        let all = db.get_column_values_all("name", &ann, "id").unwrap();
+        let none = db.get_column_values("name", &CellValue::Text("Cy".to_string()), "id");
+        let bad_search = db.get_column_values_all("nmae", &ann, "id");
+        let bad_target = db.get_column_values("name", &ann, "age");
+
+        let ids = [1.0, 3.0, 4.0].map(CellValue::Number);
+        assert_eq!(all, ids);
+        assert_eq!(
+            db.get_column_values("name", &ann, "id").unwrap(),
+            Some(ids.to_vec())
+        );
+        assert_eq!(none.unwrap(), None);
+        assert!(matches!(bad_search, Err(ExcelDbError::ColumnNotFound(c)) if c == "nmae"));
+        assert!(matches!(bad_target, Err(ExcelDbError::ColumnNotFound(c)) if c == "age"));
+    }
+
+    #[test]
+    fn omit_key_keeps_blank_columns_when_saving() {
+        let path = temp_path("omit_key.csv");
+        fs::write(&path, "name,note,age\nAnn,,30\nBob,,\n").unwrap();
+        let open = || {
+            ExcelDatabase::builder()
+                .file_path(&path)
+                .empty_cell_strategy(EmptyCellStrategy::OmitKey)
+                .build()
+        };
+        let mut db = open().unwrap();
+        let loaded = db.get_column_names().to_vec();
+        db.insert(query("name", CellValue::Text("Cy".to_string())))
+            .unwrap();
+        let reloaded = open().unwrap();
+        let header_line = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, ["name", "note", "age"]);
+        assert_eq!(header_line.lines().next(), Some("name,note,age"));
+        assert_eq!(reloaded.get_column_names(), ["name", "note", "age"]);
+        assert_eq!(reloaded.count(None), 3);
+        assert!(!reloaded.to_rows()[2].contains_key("note"));
+    }
+
+    #[test]
+    fn tsv_round_trip_keeps_text_and_loads_every_value_as_text() {
+        let path = temp_path("round_trip.tsv");
+        let db = people(&["Ann\tLee", "Bob\\2"]);
+        db.export_tsv(&path).unwrap();
+        let mut copy = people(&[]);
+        let imported = copy.import_tsv(&path);
+        fs::remove_file(&path).unwrap();
+
+        let text = |s: &str| CellValue::Text(s.to_string());
+        assert_eq!(imported.unwrap(), 2);
+        assert_eq!(copy.get_column_names(), ["id", "name"]);
+        assert_eq!(copy.to_rows()[0]["name"], text("Ann\tLee"));
+        assert_eq!(copy.to_rows()[1]["name"], text("Bob\\2"));
+        assert_eq!(db.to_rows()[0]["id"], CellValue::Number(1.0));
+        assert_eq!(copy.to_rows()[0]["id"], text("1"));
+    }
+
+    #[test]
+    fn clones_edit_their_own_rows_and_save_to_the_same_file() {
+        let path = temp_path("clone.csv");
+        fs::write(&path, "id,name\n1,Ann\n2,Bob\n").unwrap();
+        let mut db = ExcelDatabase::new(&path, None).unwrap();
+        let mut copy = db.clone();
+
+        copy.delete_at(0).unwrap();
+        let after_copy_saved = ExcelDatabase::new(&path, None).map(|d| d.count(None));
+        db.save_data().unwrap();
+        let after_original_saved = ExcelDatabase::new(&path, None).map(|d| d.count(None));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(copy.count(None), 1);
+        assert_eq!(db.count(None), 2);
+        assert_eq!(
+            db.get_row(0).unwrap()["name"],
+            CellValue::Text("Ann".to_string())
+        );
+        assert_eq!(copy.file_path, db.file_path);
+        assert_eq!(copy.get_column_names(), db.get_column_names());
+        assert_eq!(after_copy_saved.unwrap(), 1);
+        assert_eq!(after_original_saved.unwrap(), 2);
+    }
+
+    #[test]
+    fn first_matching_updates_and_deletes_touch_one_row() {
+        let mut db = people(&["Ann", "Bob", "Ann", "Ann"]);
+        let name = |n: &str| query("name", CellValue::Text(n.to_string()));
+
+        let updated = db
+            .update_first_matching(&name("Ann"), &name("Anna"))
+            .unwrap();
+        let deleted = db.delete_first_matching(&name("Ann")).unwrap();
+        let missed_update = db.update_first_matching(&name("Cid"), &name("Cy")).unwrap();
+        let missed_delete = db.delete_first_matching(&name("Cid")).unwrap();
+
+        assert!(updated);
+        assert_eq!(deleted.unwrap()["id"], CellValue::Number(3.0));
+        assert!(!missed_update);
+        assert_eq!(missed_delete, None);
+        let names: Vec<String> = db.to_rows().iter().map(|r| r["name"].to_string()).collect();
+        assert_eq!(names, ["Anna", "Bob", "Ann"]);
+    }
+
+    #[test]
+    fn rolling_extremes_slide_over_numeric_cells() {
+        let cells = [
+            CellValue::Number(3.0),
+            CellValue::Number(1.0),
+            CellValue::Text("x".to_string()),
+            CellValue::Number(4.0),
+            CellValue::Number(1.0),
+            CellValue::Number(5.0),
+        ];
+        let rows = cells.into_iter().map(|v| query("n", v)).collect();
+        let db = ExcelDatabase::from_rows(rows, vec!["n".to_string()]);
+        let n = |x: f64| Some(CellValue::Number(x));
+
+        assert_eq!(
+            db.rolling_max("n", 2).unwrap(),
+            [None, n(3.0), n(1.0), n(4.0), n(4.0), n(5.0)]
+        );
+        assert_eq!(
+            db.rolling_min("n", 3).unwrap(),
+            [None, None, n(1.0), n(1.0), n(1.0), n(1.0)]
+        );
+        assert_eq!(db.rolling_max("n", 1).unwrap()[2], None);
+        assert!(matches!(
+            db.rolling_min("n", 0),
+            Err(ExcelDbError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            db.rolling_max("m", 2),
+            Err(ExcelDbError::ColumnNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn json_column_expands_into_one_column_per_key() {
+        let cells = [
+            r#"{"a": 1, "b": "x"}"#,
+            r#"{"b": "y", "c": true}"#,
+            "not json",
+        ];
+        let rows = cells
+            .iter()
+            .map(|s| query("meta", CellValue::Text(s.to_string())))
+            .collect();
+        let mut db = ExcelDatabase::from_rows(rows, vec!["meta".to_string()]);
+
+        let added = db.expand_json_column("meta", Some("m_"), true).unwrap();
+        let rows = db.to_rows();
+
+        assert_eq!(added, ["m_a", "m_b", "m_c"]);
+        assert_eq!(db.get_column_names(), ["m_a", "m_b", "m_c"]);
+        assert_eq!(rows[0]["m_a"], CellValue::Number(1.0));
+        assert_eq!(rows[0]["m_c"], CellValue::Empty);
+        assert_eq!(rows[1]["m_b"], CellValue::Text("y".to_string()));
+        assert_eq!(rows[1]["m_c"], CellValue::Bool(true));
+        assert!(rows[2].values().all(CellValue::is_empty));
+        assert!(matches!(
+            db.expand_json_column("meta", None, false),
+            Err(ExcelDbError::ColumnNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn all_blank_columns_are_found_and_dropped() {
+        let mut db = people(&["Ann", "", "Cid"]);
+        db.add_column("note", None).unwrap();
+        db.update(&Row::new(), &query("note", CellValue::Text(String::new())))
+            .unwrap();
+        let mut cid = query("note", CellValue::Empty);
+        cid.insert("id".to_string(), CellValue::Number(4.0));
+        db.insert(cid).unwrap();
+
+        let all_null = db.columns_with_all_nulls();
+        let any_null = db.columns_with_any_nulls();
+        let dropped = db.drop_columns_with_all_nulls().unwrap();
+        let dropped_again = db.drop_columns_with_all_nulls().unwrap();
+
+        assert_eq!(all_null, ["note"]);
+        assert_eq!(any_null, ["name", "note"]);
+        assert_eq!(dropped, ["note"]);
+        assert!(dropped_again.is_empty());
+        assert_eq!(db.get_column_names(), ["id", "name"]);
+        assert!(db.to_rows().iter().all(|row| !row.contains_key("note")));
+    }
+
+    #[test]
+    fn header_style_is_written_on_save() {
+        let path = temp_path("header_style.xlsx");
+        let mut db = ExcelDatabase::create(&path, None, vec!["name".to_string()]).unwrap();
+        let style = HeaderStyle {
+            bold: true,
+            font_color: Some(RgbColor::new(255, 0, 0)),
+            background: Some(RgbColor::new(0xEE, 0xEE, 0xEE)),
+            font_size: Some(14.0),
+            ..HeaderStyle::default()
+        };
+        db.set_header_style(style).unwrap();
+        let bad_size = db.set_header_style(HeaderStyle {
+            font_size: Some(0.0),
+            ..HeaderStyle::default()
+        });
+        db.insert(query("name", CellValue::Text("Ann".to_string())))
+            .unwrap();
+        let book = reader::xlsx::read(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(bad_size, Err(ExcelDbError::InvalidParameter(_))));
+        let book = book.unwrap();
+        let sheet = book.get_sheet_by_name(&db.sheet_name).unwrap();
+        let header = sheet.get_cell(1, 1).unwrap().get_style();
+        let font = header.get_font().unwrap();
+        assert!(*font.get_bold());
+        assert_eq!(font.get_color().get_argb(), "FFFF0000");
+        assert_eq!(*font.get_size(), 14.0);
+        assert_eq!(
+            header.get_background_color().unwrap().get_argb(),
+            "FFEEEEEE"
+        );
+        let data_font = sheet.get_cell(1, 2).unwrap().get_style().get_font();
+        assert!(!data_font.is_some_and(|f| *f.get_bold()));
+    }
+
+    #[test]
+    fn rows_are_deleted_by_index_in_one_pass() {
+        let mut db = people(&["Ann", "Bob", "Cid", "Dee", "Eve"]);
+        let names = |db: &ExcelDatabase| -> Vec<String> {
+            db.to_rows().iter().map(|r| r["name"].to_string()).collect()
+        };
+
+        let out_of_bounds = db.delete_at_indices(vec![1, 5]);
+        let untouched = names(&db);
+        let removed = db.delete_at_indices(vec![4, 0, 2, 0]).unwrap();
+
+        assert!(matches!(
+            out_of_bounds,
+            Err(ExcelDbError::IndexOutOfBounds(5))
+        ));
+        assert_eq!(untouched.len(), 5);
+        let removed: Vec<String> = removed.iter().map(|r| r["name"].to_string()).collect();
+        assert_eq!(removed, ["Ann", "Cid", "Eve"]);
+        assert_eq!(names(&db), ["Bob", "Dee"]);
+    }
+
+    #[test]
+    fn rollback_restores_rows_and_leaves_the_file_alone() {
+        let path = temp_path("rollback.csv");
+        fs::write(&path, "id,name\n1,Ann\n2,Bob\n").unwrap();
+        let mut db = ExcelDatabase::new(&path, None).unwrap();
+        let before = db.to_rows().to_vec();
+        let name = |n: &str| query("name", CellValue::Text(n.to_string()));
+
+        db.begin();
+        db.insert(name("Cid")).unwrap();
+        db.update(&name("Ann"), &name("Anna")).unwrap();
+        db.delete(&name("Bob")).unwrap();
+        db.add_column("note", None).unwrap();
+        let during = fs::read_to_string(&path).unwrap();
+        db.rollback();
+        let after = fs::read_to_string(&path).unwrap();
+        db.begin();
+        db.insert(name("Dee")).unwrap();
+        db.commit().unwrap();
+        let committed = ExcelDatabase::new(&path, None).map(|d| d.count(None));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(during, "id,name\n1,Ann\n2,Bob\n");
+        assert_eq!(after, during);
+        assert!(!db.in_transaction());
+        assert_eq!(db.to_rows()[..2], before[..]);
+        assert_eq!(db.get_column_names(), ["id", "name"]);
+        assert_eq!(committed.unwrap(), 3);
+    }
+
+    #[test]
+    fn rows_sort_numerically_by_several_keys() {
+        let text = |s: &str| CellValue::Text(s.to_string());
+        let rows = [
+            ("10", "b", "1"),
+            ("9", "a", "2"),
+            ("1", "z", "3"),
+            ("9", "c", "4"),
+            ("9", "a", "5"),
+        ]
+        .iter()
+        .map(|(n, s, id)| {
+            let mut row = query("n", text(n));
+            row.insert("s".to_string(), text(s));
+            row.insert("id".to_string(), text(id));
+            row
+        })
+        .collect();
+        let headers = ["n", "s", "id"].map(String::from).to_vec();
+        let mut db = ExcelDatabase::from_rows(rows, headers);
+        let ids =
+            |rows: &[Row]| -> Vec<String> { rows.iter().map(|r| r["id"].to_string()).collect() };
+
+        let by_number = db.sorted(&[("n", SortOrder::Ascending)]);
+        let unsorted = ids(db.to_rows());
+        db.sort_by(&[("n", SortOrder::Ascending), ("s", SortOrder::Descending)]);
+
+        assert_eq!(ids(&by_number), ["3", "2", "4", "5", "1"]);
+        assert_eq!(unsorted, ["1", "2", "3", "4", "5"]);
+        assert_eq!(ids(db.to_rows()), ["3", "4", "2", "5", "1"]);
+    }
+
+    #[test]
+    fn pages_split_the_matches_and_run_past_the_end() {
+        let db = people(&["Ann", "Bob", "Ann", "Ann", "Cid", "Ann", "Ann"]);
+        let ann = query("name", CellValue::Text("Ann".to_string()));
+        let ids =
+            |page: &Page| -> Vec<CellValue> { page.rows.iter().map(|r| r["id"].clone()).collect() };
+
+        let first = db.select_page(Some(&ann), 0, 2).unwrap();
+        let last = db.select_page(Some(&ann), 2, 2).unwrap();
+        let beyond = db.select_page(Some(&ann), 3, 2).unwrap();
+        let everything = db.select_page(None, 0, 10).unwrap();
+
+        assert_eq!(ids(&first), [1.0, 3.0].map(CellValue::Number));
+        assert_eq!((first.total_matching, first.total_pages), (5, 3));
+        assert_eq!(ids(&last), [CellValue::Number(7.0)]);
+        assert!(beyond.rows.is_empty());
+        assert_eq!((beyond.page, beyond.total_pages), (3, 3));
+        assert_eq!((everything.rows.len(), everything.total_pages), (7, 1));
+        assert!(matches!(
+            db.select_page(None, 0, 0),
+            Err(ExcelDbError::InvalidPage(_))
+        ));
+    }
+
+    #[test]
+    fn column_aggregates_skip_blank_and_unparsable_cells() {
+        let cells = [
+            CellValue::Number(4.0),
+            CellValue::Text("2.5".to_string()),
+            CellValue::Text("n/a".to_string()),
+            CellValue::Empty,
+            CellValue::Number(-1.5),
+        ];
+        let mut rows: Vec<Row> = cells.into_iter().map(|v| query("amount", v)).collect();
+        for row in rows.iter_mut() {
+            row.insert("label".to_string(), CellValue::Text("x".to_string()));
+        }
+        let headers = vec!["amount".to_string(), "label".to_string()];
+        let db = ExcelDatabase::from_rows(rows, headers);
+        let agg = |value: f64| AggResult { value, skipped: 1 };
+
+        assert_eq!(db.sum_column("amount").unwrap(), agg(5.0));
+        assert_eq!(db.avg_column("amount").unwrap(), agg(5.0 / 3.0));
+        assert_eq!(db.min_column("amount").unwrap(), agg(-1.5));
+        assert_eq!(db.max_column("amount").unwrap(), agg(4.0));
+        assert!(matches!(
+            db.sum_column("label"),
+            Err(ExcelDbError::NotANumericColumn(_))
+        ));
+        assert!(matches!(
+            db.avg_column("price"),
+            Err(ExcelDbError::ColumnNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn index_stays_current_through_inserts_updates_and_deletes() {
+        let mut db = people(&["Ann", "Bob", "Ann"]);
+        let name = |n: &str| query("name", CellValue::Text(n.to_string()));
+        let count = |db: &ExcelDatabase, n: &str| db.select(Some(&name(n))).map_or(0, |r| r.len());
+        db.build_index("name").unwrap();
+
+        db.insert(name("Cid")).unwrap();
+        db.update(&name("Bob"), &name("Ann")).unwrap();
+        db.delete_at(0).unwrap();
+
+        assert!(db.has_index("name"));
+        assert_eq!(count(&db, "Ann"), 2);
+        assert_eq!(count(&db, "Bob"), 0);
+        assert_eq!(count(&db, "Cid"), 1);
+        let first_ann = db.get_column_value("name", &CellValue::Text("Ann".to_string()), "id");
+        assert_eq!(first_ann.unwrap(), Some(CellValue::Number(2.0)));
+        db.drop_index("name");
+        assert!(!db.has_index("name"));
+        assert_eq!(count(&db, "Ann"), 2);
+        assert!(matches!(
+            db.build_index("age"),
+            Err(ExcelDbError::ColumnNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn reordered_columns_are_saved_in_the_new_order() {
+        let path = temp_path("reorder.csv");
+        fs::write(&path, "id,name,age\n1,Ann,30\n").unwrap();
+        let mut db = ExcelDatabase::new(&path, None).unwrap();
+
+        let unknown = db.reorder_columns(&["age", "nmae", "id"]);
+        let repeated = db.reorder_columns(&["age", "age", "id"]);
+        let partial = db.reorder_columns(&["age", "id"]);
+        db.reorder_columns(&["age", "id", "name"]).unwrap();
+        db.save().unwrap();
+        let saved = fs::read_to_string(&path);
+        let reopened = ExcelDatabase::new(&path, None);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(unknown, Err(ExcelDbError::ColumnNotFound(c)) if c == "nmae"));
+        assert!(matches!(repeated, Err(ExcelDbError::InvalidParameter(_))));
+        assert!(matches!(partial, Err(ExcelDbError::MissingColumnsInOrder(m)) if m == ["name"]));
+        assert_eq!(saved.unwrap(), "age,id,name\n30,1,Ann\n");
+        let reopened = reopened.unwrap();
+        assert_eq!(reopened.get_column_names(), ["age", "id", "name"]);
+        let keys: Vec<&String> = reopened.to_rows()[0].keys().collect();
+        assert_eq!(keys, ["age", "id", "name"]);
+    }
+
+    #[test]
+    fn range_deletes_reject_ranges_past_the_end() {
+        let mut db = people(&["Ann", "Bob", "Cid", "Dee", "Eve", "Fay"]);
+        let names = |db: &ExcelDatabase| -> Vec<String> {
+            db.to_rows().iter().map(|r| r["name"].to_string()).collect()
+        };
+
+        let overlapping = db.delete_range(4, 7);
+        let starting_past_end = db.delete_range(6, 6);
+        let backwards = db.delete_range(3, 2);
+        let kept = names(&db);
+        let removed = db.delete_range(1, 3).unwrap();
+        let first = db.delete_first_n(1).unwrap();
+        let last = db.delete_last_n(10).unwrap();
+        let nothing_left = db.delete_first_n(1).unwrap();
+
+        assert!(matches!(
+            overlapping,
+            Err(ExcelDbError::IndexOutOfBounds(7))
+        ));
+        assert!(matches!(
+            starting_past_end,
+            Err(ExcelDbError::IndexOutOfBounds(6))
+        ));
+        assert!(matches!(backwards, Err(ExcelDbError::IndexOutOfBounds(3))));
+        assert_eq!(kept.len(), 6);
+        assert_eq!((removed, first, last, nothing_left), (2, 1, 3, 0));
+        assert!(names(&db).is_empty());
+    }
+
+    #[test]
+    fn change_log_snapshots_inserts_updates_and_deletes_until_commit() {
+        let path = temp_path("change_log.csv");
+        fs::write(&path, "id,name\n1,Ann\n").unwrap();
+        let mut db = ExcelDatabase::new(&path, None).unwrap();
+        let name = |n: &str| query("name", CellValue::Text(n.to_string()));
+        let untracked = db.pending_changes().is_none();
+        db.enable_change_tracking();
+        db.begin();
+
+        db.insert(name("Bob")).unwrap();
+        db.update(&name("Ann"), &name("Anna")).unwrap();
+        db.delete(&name("Bob")).unwrap();
+        let log = db.pending_changes().unwrap().to_vec();
+        db.commit().unwrap();
+        let after_commit = db.pending_changes().map(<[Change]>::len);
+        fs::remove_file(&path).unwrap();
+
+        let bob = CellValue::Text("Bob".to_string());
+        let ann = |n: &str| {
+            let mut row = query("id", CellValue::Text("1".to_string()));
+            row.extend(name(n));
+            row
+        };
+        assert!(untracked);
+        assert_eq!(log.len(), 3);
+        assert!(matches!(&log[0], Change::Insert(row) if row["name"] == bob));
+        assert_eq!(
+            log[1],
+            Change::Update {
+                before: ann("Ann"),
+                after: ann("Anna")
+            }
+        );
+        assert!(matches!(&log[2], Change::Delete(row) if row["name"] == bob));
+        assert_eq!(after_commit, Some(0));
+    }
+
+    #[cfg(feature = "xls")]
+    #[test]
+    fn xls_fixture_loads_like_xlsx_and_saves_as_xlsx() {
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/people.xls");
+        let path = temp_path("people.xls");
+        fs::copy(fixture, &path).unwrap();
+        let sheet = Some("People".to_string());
+        let mut db = ExcelDatabase::from_xls(&path, sheet.clone()).unwrap();
+        let loaded = db.to_rows().to_vec();
+        let saved_to = PathBuf::from(&db.file_path);
+        db.insert(query("name", CellValue::Text("Dee".to_string())))
+            .unwrap();
+        let reopened = ExcelDatabase::new(&saved_to, sheet.clone());
+        let again = ExcelDatabase::from_xls(&path, sheet);
+        let unsupported = ExcelDatabase::from_xls(temp_path("people.ods"), None);
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&saved_to).unwrap();
+
+        assert_eq!(db.get_column_names(), ["id", "name", "score"]);
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded[0]["id"], CellValue::Number(1.0));
+        assert_eq!(loaded[0]["name"], CellValue::Text("Ann".to_string()));
+        assert_eq!(loaded[2]["score"], CellValue::Number(8.25));
+        assert_eq!(saved_to, path.with_extension("xlsx"));
+        let reopened = reopened.unwrap();
+        assert_eq!(reopened.get_column_names(), db.get_column_names());
+        assert_eq!(reopened.to_rows()[..3], loaded[..]);
+        assert_eq!(reopened.count(None), 4);
+        assert!(matches!(again, Err(ExcelDbError::FileAlreadyExists(_))));
+        assert!(matches!(
+            unsupported,
+            Err(ExcelDbError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn every_empty_cell_strategy_agrees_with_count_empty_cells() {
+        let path = temp_path("strategies.csv");
+        fs::write(&path, "name,note,age\nAnn,,30\nBob, ,\nCid,x,5\n").unwrap();
+        let load = |strategy| {
+            ExcelDatabase::builder()
+                .file_path(&path)
+                .empty_cell_strategy(strategy)
+                .build()
+                .unwrap()
+        };
+        let empty_string = load(EmptyCellStrategy::EmptyString);
+        let omit_key = load(EmptyCellStrategy::OmitKey);
+        let skip_row = load(EmptyCellStrategy::SkipRow);
+        let empty = load(EmptyCellStrategy::Empty);
+        fs::remove_file(&path).unwrap();
+
+        let counts =
+            |db: &ExcelDatabase| ["name", "note", "age", "city"].map(|c| db.count_empty_cells(c));
+        assert_eq!(
+            empty_string.to_rows()[0]["note"],
+            CellValue::Text(String::new())
+        );
+        assert_eq!(counts(&empty_string), [0, 2, 1, 3]);
+        assert!(!omit_key.to_rows()[0].contains_key("note"));
+        assert!(!omit_key.to_rows()[1].contains_key("age"));
+        assert_eq!(counts(&omit_key), [0, 2, 1, 3]);
+        assert_eq!(skip_row.count(None), 1);
+        assert_eq!(counts(&skip_row), [0, 0, 0, 1]);
+        assert_eq!(empty.to_rows()[1]["age"], CellValue::Empty);
+        assert_eq!(counts(&empty), [0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn column_widths_are_stored_in_the_saved_workbook() {
+        let path = temp_path("widths.xlsx");
+        let headers = ["id", "name", "city"].map(String::from).to_vec();
+        let mut db = ExcelDatabase::create(&path, None, headers).unwrap();
+        let mut row = query("name", CellValue::Text("Bartholomew".to_string()));
+        row.insert("city".to_string(), CellValue::Text("Oslo".to_string()));
+        db.insert(row).unwrap();
+        db.auto_fit_columns();
+        db.set_column_width("id", 6.5).unwrap();
+        let unknown = db.set_column_width("age", 10.0);
+        let negative = db.set_column_width("id", -1.0);
+        db.save().unwrap();
+        let book = reader::xlsx::read(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(unknown, Err(ExcelDbError::ColumnNotFound(_))));
+        assert!(matches!(negative, Err(ExcelDbError::InvalidParameter(_))));
+        let book = book.unwrap();
+        let sheet = book.get_sheet_by_name(&db.sheet_name).unwrap();
+        let width = |col: &str| *sheet.get_column_dimension(col).unwrap().get_width();
+        assert_eq!(width("A"), 6.5);
+        assert_eq!(width("B"), 11.0 + AUTO_FIT_PADDING);
+        assert_eq!(width("C"), 4.0 + AUTO_FIT_PADDING);
+    }
+
+    #[test]
+    fn update_counts_only_rows_that_changed() {
+        let mut db = people(&["Ann", "Bob", "Ann", "Cid"]);
+        let name = |n: &str| query("name", CellValue::Text(n.to_string()));
+        db.update(&name("Cid"), &name("Ann")).unwrap();
+
+        let no_op = db.update(&name("Ann"), &name("Ann")).unwrap();
+        let no_match = db.update(&name("Dee"), &name("Eve")).unwrap();
+        let real = db.update(&name("Ann"), &name("Anna")).unwrap();
+        let mut partly_same = name("Anna");
+        partly_same.insert("id".to_string(), CellValue::Number(1.0));
+        let partial = db.update(&name("Anna"), &partly_same).unwrap();
+
+        assert_eq!((no_op, no_match, real, partial), (0, 0, 3, 2));
+        assert!(ExcelDatabase::rows_differ(&name("Ann"), &name("Anna")));
+        assert!(!ExcelDatabase::rows_differ(&name("Ann"), &name("Ann")));
+    }
+
+    #[test]
+    fn frozen_panes_are_saved_and_survive_later_saves() {
+        let path = temp_path("freeze.xlsx");
+        let headers = vec!["id".to_string(), "name".to_string()];
+        let mut db = ExcelDatabase::create(&path, None, headers).unwrap();
+        let sheet_name = db.sheet_name.clone();
+        let pane = || {
+            let book = reader::xlsx::read(&path).unwrap();
+            let sheet = book.get_sheet_by_name(&sheet_name).unwrap();
+            sheet.get_sheet_view().get_pane().map(|pane| {
+                (
+                    *pane.get_horizontal_split(),
+                    *pane.get_vertical_split(),
+                    pane.get_top_left_cell().to_string(),
+                )
+            })
+        };
+
+        db.freeze_rows(1).unwrap();
+        let header_frozen = pane();
+        db.freeze_columns(1).unwrap();
+        let column_frozen = pane();
+        db.freeze_pane(2, 1).unwrap();
+        db.insert(query("name", CellValue::Text("Ann".to_string())))
+            .unwrap();
+        let both_frozen = pane();
+        db.clear_freeze().unwrap();
+        let cleared = pane();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(header_frozen, Some((0.0, 1.0, "A2".to_string())));
+        assert_eq!(column_frozen, Some((1.0, 0.0, "B1".to_string())));
+        assert_eq!(both_frozen, Some((1.0, 2.0, "B3".to_string())));
+        assert_eq!(cleared, None);
+    }
+
+    #[test]
+    fn aliased_headers_are_renamed_on_load_and_found_by_either_name() {
+        let path = temp_path("aliases.csv");
+        fs::write(&path, "id,client_name\n1,Ann\n2,Bob\n").unwrap();
+        let mut db = ExcelDatabase::new(&path, None).unwrap();
+        let ann = CellValue::Text("Ann".to_string());
+
+        let self_alias = db.add_column_alias("name", "name");
+        db.add_column_alias("name", "client_name").unwrap();
+        let by_name = db
+            .select(Some(&query("name", ann.clone())))
+            .map(|r| r.len());
+        let by_alias = db.get_column_value("client_name", &ann, "id").unwrap();
+        db.reload().unwrap();
+        let reloaded = db.get_column_names();
+        db.remove_column_alias("client_name");
+        let without_alias = db.select(Some(&query("client_name", ann)));
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(self_alias, Err(ExcelDbError::InvalidParameter(_))));
+        assert_eq!(by_name, Some(1));
+        assert_eq!(by_alias, Some(CellValue::Text("1".to_string())));
+        assert_eq!(reloaded, ["id", "name"]);
+        assert!(db.list_aliases().is_empty());
+        assert!(without_alias.is_none());
+    }
+
+    #[test]
+    fn named_ranges_resolve_to_rows() {
+        let path = temp_path("named.xlsx");
+        let headers = vec!["id".to_string(), "name".to_string()];
+        let mut db = ExcelDatabase::create(&path, None, headers).unwrap();
+        for name in ["Ann", "Bob", "Cid"] {
+            db.insert(query("name", CellValue::Text(name.to_string())))
+                .unwrap();
+        }
+        let mut book = reader::xlsx::read(&path).unwrap();
+        let address = format!("{}!$A$3:$B$4", db.sheet_name);
+        book.add_defined_name("SalesData", address.as_str())
+            .unwrap();
+        writer::xlsx::write(&book, &path).unwrap();
+        db.reload().unwrap();
+
+        let names = db.list_named_ranges();
+        let rows = db.get_named_range("salesdata");
+        let missing = db.get_named_range("Nope");
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(names.unwrap(), ["SalesData"]);
+        let names: Vec<String> = rows
+            .unwrap()
+            .iter()
+            .map(|r| r["name"].to_string())
+            .collect();
+        assert_eq!(names, ["Bob", "Cid"]);
+        assert!(matches!(missing, Err(ExcelDbError::NamedRangeNotFound(n)) if n == "Nope"));
+        assert!(matches!(
+            people(&["Ann"]).list_named_ranges(),
+            Err(ExcelDbError::NoFilePath)
+        ));
+    }
+
+    #[test]
+    fn two_level_headers_flatten_across_groups() {
+        let path = temp_path("quarters.csv");
+        let csv = "Q1,,Q2,\nRevenue,Profit,Revenue,Profit\n10,2,12,3\n11,4,9,1\n";
+        fs::write(&path, csv).unwrap();
+        let db = ExcelDatabase::load_with_multilevel_headers(&path, None, 2);
+        fs::remove_file(&path).unwrap();
+
+        let db = db.unwrap();
+        let headers = ["Q1_Revenue", "Q1_Profit", "Q2_Revenue", "Q2_Profit"];
+        assert_eq!(db.get_column_names(), headers);
+        assert_eq!(db.count(None), 2);
+        let second: Vec<String> = headers
+            .iter()
+            .map(|h| db.to_rows()[1][*h].to_string())
+            .collect();
+        assert_eq!(second, ["11", "4", "9", "1"]);
+    }
+
+    #[test]
+    fn date_sequence_violations_are_reported_by_row() {
+        let dates = [
+            "2024-01-01",
+            "2024-01-05",
+            "2024-01-05",
+            "",
+            "2024-01-20",
+            "2024-01-02",
+        ];
+        let rows = dates
+            .iter()
+            .map(|d| query("day", CellValue::Text(d.to_string())))
+            .collect();
+        let db = ExcelDatabase::from_rows(rows, vec!["day".to_string()]);
+        let rows_of = |check| -> Vec<usize> {
+            let found = db.validate_date_sequence("day", check).unwrap();
+            found.into_iter().map(|(row, _)| row).collect()
+        };
+
+        assert_eq!(rows_of(DateSequenceCheck::StrictlyAscending), [2, 5]);
+        assert_eq!(rows_of(DateSequenceCheck::StrictlyDescending), [1, 2, 4]);
+        assert_eq!(rows_of(DateSequenceCheck::NoDuplicates), [2]);
+        assert_eq!(
+            rows_of(DateSequenceCheck::NoGapsLargerThan(Duration::days(7))),
+            [4, 5]
+        );
+        assert!(matches!(
+            db.validate_date_sequence("when", DateSequenceCheck::NoDuplicates),
+            Err(ExcelDbError::ColumnNotFound(_))
+        ));
+    }
+}